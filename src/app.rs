@@ -0,0 +1,446 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use futures::future::join_all;
+use tokio::time::{self, MissedTickBehavior};
+
+use crate::{
+    config::{Config, EventStyle, TranslatorBackend},
+    destination::Destination,
+    maintenance, metrics,
+    models::{Event, EventType},
+    notify::{self, queue::Delivery, stdout::StdoutNotifier, Notifier},
+    render::{self, History},
+    retry::RetryPolicy,
+    server::{self, Health, SharedHealth},
+    services::ServiceMonitor,
+    source::StatusSource,
+    state::{self, ForgetPolicy, SavedEvent, SavedEvents, StateStore},
+    status::{StatusClient, StatusError},
+    translate::{self, translate_all, Translator},
+    watcher::{detect, ChangeKind},
+};
+
+/// Everything a poll needs, shared by the commands.
+pub struct App {
+    config: Config,
+    source: Box<dyn StatusSource>,
+    retry_policy: RetryPolicy,
+    translator: Box<dyn Translator>,
+    destinations: Vec<Destination>,
+    event_styles: HashMap<EventType, EventStyle>,
+    state_store: Box<dyn StateStore>,
+    saved_events: SavedEvents,
+    service_monitor: ServiceMonitor,
+    health: SharedHealth,
+    // Off for dry runs and replays, which mustn't change what the daemon remembers.
+    persist: bool,
+}
+
+impl App {
+    /// A dry run prints the notifications instead of sending them and keeps the state as it is.
+    pub fn new(config: Config, dry_run: bool) -> Result<Self> {
+        if config.translator.backend() == TranslatorBackend::Passthrough {
+            warn!("No translator configured, events will be posted untranslated");
+        }
+
+        let reqwest_client = reqwest::Client::new();
+        let source = StatusClient::new(reqwest_client.clone(), config.status_api_url());
+        let translator = translate::build(&config.translator, reqwest_client.clone());
+        let destinations = config
+            .destinations
+            .iter()
+            .map(|destination| {
+                let notifier: Box<dyn Notifier> = if dry_run {
+                    Box::new(StdoutNotifier::new(&destination.name))
+                } else {
+                    notify::build(destination, reqwest_client.clone())?
+                };
+                Destination::new(
+                    destination,
+                    &config.target_lang,
+                    notifier,
+                    RetryPolicy::from(&config.delivery_retry),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let state_store = state::open(&config.state)?;
+        let state = state_store.load()?;
+        let saved_events = state.events;
+        info!("Loaded {} saved events", saved_events.len());
+
+        let health = Health::shared();
+        health.lock().unwrap().events = saved_events.clone();
+
+        Ok(Self {
+            retry_policy: RetryPolicy::from(&config.retry),
+            event_styles: config.event_styles(),
+            config,
+            source: Box::new(source),
+            translator,
+            destinations,
+            state_store,
+            saved_events,
+            service_monitor: ServiceMonitor::new(state.services),
+            health,
+            persist: !dry_run,
+        })
+    }
+
+    /// Polls `source` instead of the status API.
+    pub fn set_source(&mut self, source: Box<dyn StatusSource>) {
+        self.source = source;
+    }
+
+    /// Starts from scratch without touching the saved state, so every event counts as new.
+    pub fn forget_state(&mut self) {
+        self.saved_events.clear();
+        self.persist = false;
+    }
+
+    /// Polls until SIGINT/SIGTERM, checking maintenance reminders in between.
+    pub async fn run(mut self) -> Result<()> {
+        if self.config.server.enabled {
+            server::start(
+                self.config.server.listen()?,
+                self.health.clone(),
+                self.config
+                    .server
+                    .ready_threshold(self.config.poll_interval()),
+            )?;
+        }
+
+        let mut interval = time::interval(self.config.poll_interval());
+        // Retries can outlast a tick, don't make up for it with a burst of polls afterwards
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut maintenance_interval = time::interval(self.config.maintenance.check_interval());
+        maintenance_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut shutdown = tokio::spawn(shutdown_signal());
+        let timeout = self.config.shutdown.timeout();
+
+        loop {
+            // Reminders are checked in between polls, both share the saved events
+            let remind = tokio::select! {
+                _ = interval.tick() => false,
+                _ = maintenance_interval.tick(), if self.config.maintenance.enabled => true,
+                _ = &mut shutdown => break,
+            };
+
+            let work = async {
+                if remind {
+                    self.remind().await;
+                } else if let Err(e) = self.poll().await {
+                    error!("Status API down, skipping this poll: {e}");
+                }
+            };
+            tokio::pin!(work);
+
+            // A signal in the middle of a poll lets its deliveries finish, so their messages are saved
+            let interrupted = tokio::select! {
+                _ = &mut work => false,
+                _ = &mut shutdown => {
+                    info!("Shutdown requested, waiting for the pending deliveries");
+                    if time::timeout(timeout, work).await.is_err() {
+                        warn!("Gave up on the pending deliveries after {timeout:?}");
+                    }
+                    true
+                }
+            };
+            if interrupted {
+                break;
+            }
+        }
+
+        self.shutdown(timeout).await;
+        Ok(())
+    }
+
+    // Saves the state, then tells the `[shutdown] notify` destination that we're going offline.
+    async fn shutdown(&mut self, timeout: Duration) {
+        info!("Shutting down");
+        self.save();
+
+        let destination = self.config.shutdown.notify.as_ref().and_then(|name| {
+            self.destinations
+                .iter()
+                .find(|destination| destination.name == *name)
+        });
+        if let Some(destination) = destination {
+            let notice = destination.deliver(&render::offline(destination.locale), None);
+            if time::timeout(timeout, notice).await.is_err() {
+                warn!(
+                    "[{}] Gave up on the offline notice after {timeout:?}",
+                    destination.name
+                );
+            }
+        }
+    }
+
+    /// Checks the services, then fetches the events and sends what changed.
+    pub async fn poll(&mut self) -> Result<(), StatusError> {
+        let poll_time = Utc::now();
+        self.health.lock().unwrap().last_poll = Some(poll_time);
+
+        if self.config.services.enabled {
+            let changed = self
+                .service_monitor
+                .poll(self.source.as_ref(), &self.retry_policy, &self.destinations)
+                .await;
+            if changed {
+                self.save();
+            }
+        }
+
+        // The API being down says nothing about the events, so nothing may be compared
+        // against (or cleaned up because of) a list we couldn't fetch: wait for the next poll
+        let events = match self.source.messages_with_retry(&self.retry_policy).await {
+            Ok(events) => events,
+            Err(e) => {
+                self.health.lock().unwrap().last_error = Some(e.to_string());
+                return Err(e);
+            }
+        };
+        {
+            let mut health = self.health.lock().unwrap();
+            health.last_success = Some(poll_time);
+            health.last_error = None;
+        }
+
+        self.process(&events).await;
+        Ok(())
+    }
+
+    // Sends what changed in a complete list of the events currently on the status page.
+    async fn process(&mut self, events: &[Event]) {
+        if events.is_empty() {
+            debug!("No events listed on the status page");
+        }
+
+        let metrics = metrics::get();
+        metrics.events_listed.reset();
+        for event in events.iter() {
+            metrics
+                .events_listed
+                .with_label_values(&[event.event_type.name()])
+                .inc();
+        }
+
+        // Every notification is queued before waiting for any of them: each destination works through its own
+        // queue in order, so one that is rate limited or failing doesn't hold back the others
+        let mut updates = Vec::new();
+        let mut deliveries = Vec::new();
+
+        for event in events.iter() {
+            let saved_event = self.saved_events.get(&event.id).cloned();
+            let change = detect(saved_event.as_ref(), event);
+            let saved_event = saved_event.unwrap_or_default();
+
+            let accepting = self
+                .destinations
+                .iter()
+                .filter(|destination| destination.accepts(event.event_type));
+            let (targets, revisions): (Vec<&Destination>, u32) = match &change {
+                Some(change) => {
+                    metrics
+                        .events
+                        .with_label_values(&[event.event_type.name(), change.kind.name()])
+                        .inc();
+                    if change.kind == ChangeKind::New {
+                        info!("New {} event {}", event.event_type, event.id);
+                        (accepting.collect(), 0)
+                    } else {
+                        info!("Event {} changed: {}", event.id, change.changes);
+                        // The resolution itself isn't an update of the message
+                        let revisions = saved_event.revisions + u32::from(change.changes.content);
+                        (accepting.collect(), revisions)
+                    }
+                }
+                // Nothing we display changed, only the destinations that missed it are tried again
+                None if !saved_event.pending.is_empty() => {
+                    info!(
+                        "Retrying event {} for {}",
+                        event.id,
+                        saved_event.pending.join(", ")
+                    );
+                    let targets = accepting
+                        .filter(|destination| saved_event.pending.contains(&destination.name))
+                        .collect();
+                    (targets, saved_event.revisions)
+                }
+                None => continue,
+            };
+
+            let translations = translate_all(
+                self.translator.as_ref(),
+                &event.content,
+                targets
+                    .iter()
+                    .map(|destination| destination.language.as_str()),
+            )
+            .await;
+
+            // Each destination edits the message it already posted for this event
+            let update = updates.len();
+            for destination in targets {
+                let message = saved_event.messages.get(&destination.name).cloned();
+                let history = History { revisions };
+                let notification = render::incident(
+                    event,
+                    &translations[&destination.language],
+                    destination.locale,
+                    self.event_styles.get(&event.event_type),
+                    &history,
+                );
+
+                deliveries.push(async move {
+                    let delivery = destination.deliver(&notification, message).await;
+                    (update, destination.name.clone(), delivery)
+                });
+            }
+            updates.push((event, saved_event, revisions));
+        }
+
+        let mut delivered: Vec<Vec<(String, Delivery)>> =
+            updates.iter().map(|_| Vec::new()).collect();
+        for (update, name, delivery) in join_all(deliveries).await {
+            delivered[update].push((name, delivery));
+        }
+
+        let mut state_changed = !updates.is_empty();
+        for ((event, saved_event, revisions), deliveries) in updates.into_iter().zip(delivered) {
+            let mut messages = saved_event.messages.clone();
+            let mut pending = Vec::new();
+            for (name, delivery) in deliveries {
+                if !delivery.ok {
+                    pending.push(name.clone());
+                }
+                match delivery.message {
+                    Some(message) => messages.insert(name, message),
+                    None => messages.remove(&name),
+                };
+            }
+
+            self.saved_events.insert(
+                event.id.clone(),
+                SavedEvent {
+                    content: event.content.clone(),
+                    event_type: event.event_type,
+                    solve_time: event.solve_time,
+                    messages,
+                    pending,
+                    revisions,
+                    maintenance: maintenance::schedule(
+                        event,
+                        saved_event.maintenance.as_ref(),
+                        &self.config.maintenance,
+                        Utc::now(),
+                    ),
+                    ..Default::default()
+                },
+            );
+        }
+
+        // `events` is the complete list, so events missing from it are really gone (or about to be)
+        state_changed |= state::forget_missing(
+            &mut self.saved_events,
+            events,
+            &ForgetPolicy::from(&self.config.state),
+            Utc::now(),
+        );
+
+        if state_changed {
+            self.save();
+        }
+    }
+
+    /// Sends the maintenance reminders that became due.
+    pub async fn remind(&mut self) {
+        if !self.config.maintenance.enabled {
+            return;
+        }
+
+        let changed = maintenance::remind(
+            &mut self.saved_events,
+            &self.config.maintenance,
+            &self.destinations,
+            self.translator.as_ref(),
+            &self.event_styles,
+            Utc::now(),
+        )
+        .await;
+        if changed {
+            self.save();
+        }
+    }
+
+    fn save(&mut self) {
+        if self.persist {
+            if let Err(e) = self
+                .state_store
+                .save(&self.saved_events, self.service_monitor.states())
+            {
+                error!("Failed to save state: {e:#}");
+            }
+        }
+        self.health.lock().unwrap().events = self.saved_events.clone();
+    }
+
+    /// Sends a sample notification to the destinations (or the one named), failing if any of them didn't get it.
+    pub async fn test_webhook(&self, name: Option<&str>) -> Result<()> {
+        let destinations: Vec<&Destination> = self
+            .destinations
+            .iter()
+            .filter(|destination| name.is_none_or(|name| destination.name == name))
+            .collect();
+        if destinations.is_empty() {
+            match name {
+                Some(name) => bail!("No destination named \"{name}\""),
+                None => bail!("No destination configured"),
+            }
+        }
+
+        let mut failed = 0;
+        for destination in destinations {
+            match destination.test(&render::sample(destination.locale)).await {
+                Ok(Some(id)) => println!("[{}] ok: message {id}", destination.name),
+                Ok(None) => println!("[{}] ok", destination.name),
+                Err(e) => {
+                    println!("[{}] failed: {e:#}", destination.name);
+                    failed += 1;
+                }
+            }
+        }
+
+        if failed > 0 {
+            bail!("{failed} destination(s) failed");
+        }
+        Ok(())
+    }
+}
+
+// Resolves on SIGINT or SIGTERM (what `docker stop` sends).
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => error!("Failed to listen for SIGTERM, only SIGINT stops gracefully: {e}"),
+        }
+    }
+
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        error!("Failed to listen for SIGINT, stopping gracefully is disabled: {e}");
+        futures::future::pending::<()>().await;
+    }
+}
@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "Posts the Escape from Tarkov status page to chat services"
+)]
+pub struct Cli {
+    /// Config file, defaults to $TARKOV_STATUS_CONFIG then ./config.toml
+    #[arg(short, long, global = true)]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Poll the status page forever (the default)
+    Run,
+    /// Poll once, send what changed and exit, e.g. from cron
+    Once,
+    /// Poll once and print the notifications instead of sending them, leaving the state untouched
+    DryRun,
+    /// Feed a captured /api/message/list response through the pipeline, as if nothing was seen before
+    Replay {
+        file: PathBuf,
+        /// Print the notifications instead of sending them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Send a sample notification to check that destinations are set up right
+    TestWebhook {
+        /// Only test the destination with this name
+        #[arg(long)]
+        destination: Option<String>,
+    },
+}
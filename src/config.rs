@@ -0,0 +1,1058 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::{locale, models::EventType};
+
+// Scalar options can be overridden by an environment variable made of this prefix, the section and the option name
+// in uppercase, e.g. TARKOV_STATUS_WEBHOOK_URL or TARKOV_STATUS_STATE_BACKEND. Destinations, templates and event
+// styles can only be set in the file.
+const ENV_PREFIX: &str = "TARKOV_STATUS_";
+
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // Shorthand for a single destination named "default".
+    pub webhook_url: Option<String>,
+    // Shorthand for the deepl-free translator.
+    pub deepl_api_key: Option<String>,
+    // Seconds between two polls of the status API.
+    pub poll_interval: u64,
+    pub status_api_url: String,
+    pub target_lang: String,
+    pub state: StateConfig,
+    pub destinations: Vec<DestinationConfig>,
+    pub translator: TranslatorConfig,
+    // Retries of a failed status API poll, within the same tick.
+    pub retry: RetryConfig,
+    // Retries of rate limited or failed deliveries, per destination.
+    pub delivery_retry: RetryConfig,
+    pub services: ServicesConfig,
+    pub maintenance: MaintenanceConfig,
+    pub server: ServerConfig,
+    pub shutdown: ShutdownConfig,
+    // Keyed by event type name (e.g. "ServerIssues") or raw type code.
+    pub event_styles: HashMap<String, EventStyle>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            deepl_api_key: None,
+            poll_interval: 30,
+            status_api_url: "https://status.escapefromtarkov.com".to_string(),
+            target_lang: "FR".to_string(),
+            state: StateConfig::default(),
+            destinations: Vec::new(),
+            translator: TranslatorConfig::default(),
+            retry: RetryConfig::default(),
+            // Discord asks for waits of up to a minute when a webhook is rate limited
+            delivery_retry: RetryConfig {
+                max_attempts: 5,
+                initial_delay_ms: 1000,
+                max_delay_ms: 60000,
+            },
+            services: ServicesConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            server: ServerConfig::default(),
+            shutdown: ShutdownConfig::default(),
+            event_styles: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifierKind {
+    #[default]
+    Discord,
+    Slack,
+    Telegram,
+    Matrix,
+    Json,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DestinationConfig {
+    pub name: String,
+    #[serde(default)]
+    pub kind: NotifierKind,
+    // Webhook URL for discord, slack and json, homeserver for matrix, optional Bot API base URL for telegram.
+    pub url: Option<String>,
+    // Defaults to the global target_lang.
+    pub language: Option<String>,
+    // Language of the labels around the translated content, defaults to the one of `language`.
+    pub locale: Option<String>,
+    pub username: Option<String>,
+    pub avatar_url: Option<String>,
+    // Names of the event types to forward (e.g. "ServerIssues"), every type when empty.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    // Also receives service and global status changes, which `event_types` doesn't filter. True by default.
+    pub services: Option<bool>,
+    // telegram only
+    pub bot_token: Option<String>,
+    pub chat_id: Option<String>,
+    // matrix only
+    pub access_token: Option<String>,
+    pub room_id: Option<String>,
+    // Handlebars templates replacing parts of the default layout.
+    pub template: Option<TemplateConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemplateConfig {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    // Should render to "#rrggbb" or a decimal number.
+    pub color: Option<String>,
+    pub thumbnail: Option<String>,
+    pub url: Option<String>,
+    // Replace every default field when not empty.
+    pub fields: Vec<FieldTemplateConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct FieldTemplateConfig {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub inline: bool,
+}
+
+impl DestinationConfig {
+    // Every other option left unset, e.g. `DestinationConfig { url, ..DestinationConfig::new(name, kind) }`.
+    pub fn new(name: &str, kind: NotifierKind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            url: None,
+            language: None,
+            locale: None,
+            username: None,
+            avatar_url: None,
+            event_types: Vec::new(),
+            services: None,
+            bot_token: None,
+            chat_id: None,
+            access_token: None,
+            room_id: None,
+            template: None,
+        }
+    }
+
+    fn from_webhook_url(url: &str) -> Self {
+        Self {
+            url: Some(url.to_string()),
+            ..Self::new("default", NotifierKind::Discord)
+        }
+    }
+
+    pub fn language<'a>(&'a self, default_language: &'a str) -> &'a str {
+        self.language.as_deref().unwrap_or(default_language)
+    }
+
+    pub fn locale<'a>(&'a self, default_language: &'a str) -> &'a str {
+        self.locale
+            .as_deref()
+            .unwrap_or_else(|| self.language(default_language))
+    }
+
+    fn validate(&self) -> Result<()> {
+        let required = match self.kind {
+            NotifierKind::Discord | NotifierKind::Slack | NotifierKind::Json => {
+                vec![("url", &self.url)]
+            }
+            NotifierKind::Telegram => {
+                vec![("bot_token", &self.bot_token), ("chat_id", &self.chat_id)]
+            }
+            NotifierKind::Matrix => vec![
+                ("url", &self.url),
+                ("access_token", &self.access_token),
+                ("room_id", &self.room_id),
+            ],
+        };
+        for (field, value) in required {
+            if value.is_none() {
+                bail!("Destination \"{}\": missing {field}", self.name);
+            }
+        }
+
+        if let Some(url) = self.url.as_ref().filter(|url| !is_http_url(url)) {
+            bail!(
+                "Destination \"{}\": url must be an http(s) URL, got \"{url}\"",
+                self.name
+            );
+        }
+        if let Some(name) = self
+            .event_types
+            .iter()
+            .find(|name| EventType::from_name(name).is_none())
+        {
+            bail!(
+                "Destination \"{}\": unknown event type \"{name}\", expected one of {} or a type code",
+                self.name,
+                EventType::names().join(", ")
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TranslatorBackend {
+    DeeplFree,
+    DeeplPro,
+    Libretranslate,
+    Google,
+    #[serde(alias = "none")]
+    Passthrough,
+}
+
+impl TranslatorBackend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DeeplFree => "deepl-free",
+            Self::DeeplPro => "deepl-pro",
+            Self::Libretranslate => "libretranslate",
+            Self::Google => "google",
+            Self::Passthrough => "none",
+        }
+    }
+}
+
+impl FromStr for TranslatorBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "deepl-free" => Ok(Self::DeeplFree),
+            "deepl-pro" => Ok(Self::DeeplPro),
+            "libretranslate" => Ok(Self::Libretranslate),
+            "google" => Ok(Self::Google),
+            "passthrough" | "none" => Ok(Self::Passthrough),
+            _ => bail!("Unknown translator backend \"{s}\", expected deepl-free, deepl-pro, libretranslate, google or none"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct TranslatorConfig {
+    // When unset, deepl-free is used if `deepl_api_key` is set, otherwise translation is disabled.
+    pub backend: Option<TranslatorBackend>,
+    pub api_key: Option<String>,
+    // Overrides the backend's API base URL, required for libretranslate.
+    pub url: Option<String>,
+    pub cache: TranslationCacheConfig,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct TranslationCacheConfig {
+    // Number of translations kept in memory, 0 disables the cache.
+    pub size: usize,
+    // Keeps the cache across restarts when set.
+    pub path: Option<PathBuf>,
+}
+
+impl Default for TranslationCacheConfig {
+    fn default() -> Self {
+        Self {
+            size: 1000,
+            path: None,
+        }
+    }
+}
+
+impl TranslatorConfig {
+    pub fn backend(&self) -> TranslatorBackend {
+        self.backend.unwrap_or(TranslatorBackend::Passthrough)
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self.backend() {
+            TranslatorBackend::DeeplFree
+            | TranslatorBackend::DeeplPro
+            | TranslatorBackend::Google
+                if self.api_key.is_none() =>
+            {
+                bail!(
+                    "The {} translator requires an api_key",
+                    self.backend().name()
+                )
+            }
+            TranslatorBackend::Libretranslate if self.url.is_none() => {
+                bail!("The libretranslate translator requires a url")
+            }
+            _ => {}
+        }
+        if let Some(url) = self.url.as_ref().filter(|url| !is_http_url(url)) {
+            bail!("translator url must be an http(s) URL, got \"{url}\"");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StateBackend {
+    Memory,
+    Json,
+    Sqlite,
+}
+
+impl FromStr for StateBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "memory" => Ok(Self::Memory),
+            "json" => Ok(Self::Json),
+            "sqlite" => Ok(Self::Sqlite),
+            _ => bail!("Unknown state backend \"{s}\", expected memory, json or sqlite"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct StateConfig {
+    pub backend: StateBackend,
+    // Defaults to `state.json` or `state.db` depending on the backend.
+    pub path: Option<PathBuf>,
+    // Consecutive successful polls an event must be missing from before it is forgotten.
+    pub forget_after_polls: u32,
+    // Also forget events missing for this long, even before forget_after_polls is reached.
+    pub forget_after_minutes: Option<u64>,
+}
+
+impl Default for StateConfig {
+    fn default() -> Self {
+        Self {
+            backend: StateBackend::Json,
+            path: None,
+            forget_after_polls: 3,
+            forget_after_minutes: None,
+        }
+    }
+}
+
+impl StateConfig {
+    pub fn path(&self) -> PathBuf {
+        match (&self.path, self.backend) {
+            (Some(path), _) => path.clone(),
+            (None, StateBackend::Sqlite) => PathBuf::from("state.db"),
+            (None, _) => PathBuf::from("state.json"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventStyle {
+    // "#rrggbb", used while the event is ongoing, resolved events keep the usual color.
+    pub color: Option<String>,
+    // Put in front of the title.
+    pub emoji: Option<String>,
+    // Replaces the localized name of the type.
+    pub title: Option<String>,
+}
+
+impl EventStyle {
+    pub fn color(&self) -> Option<u32> {
+        self.color.as_deref().and_then(parse_color)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServicesConfig {
+    // Notifies about services going down or recovering, on every poll.
+    pub enabled: bool,
+}
+
+impl Default for ServicesConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaintenanceConfig {
+    // Follows up on announced maintenance windows.
+    pub enabled: bool,
+    // Minutes before the start of the window to send a reminder at.
+    pub reminders: Vec<u64>,
+    // Notify when the window starts, and when it is still ongoing past its planned end.
+    pub started: bool,
+    pub overrun: bool,
+    // Seconds between two checks for due reminders.
+    pub check_interval: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reminders: vec![60, 10],
+            started: true,
+            overrun: true,
+            check_interval: 30,
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_secs(self.check_interval)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    // Serves /healthz, /readyz and /state.
+    pub enabled: bool,
+    pub listen: String,
+    // Seconds without a successful poll before /readyz fails, 3 poll intervals by default.
+    pub ready_threshold: Option<u64>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "0.0.0.0:8080".to_string(),
+            ready_threshold: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn listen(&self) -> Result<SocketAddr> {
+        self.listen.parse().with_context(|| {
+            format!(
+                "server.listen must be an address like 0.0.0.0:8080, got \"{}\"",
+                self.listen
+            )
+        })
+    }
+
+    pub fn ready_threshold(&self, poll_interval: Duration) -> Duration {
+        self.ready_threshold
+            .map(Duration::from_secs)
+            .unwrap_or(poll_interval * 3)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+    // Seconds to wait for pending deliveries on SIGINT/SIGTERM, then again for the offline notice.
+    pub timeout: u64,
+    // Destination told that the bot is going offline, none by default.
+    pub notify: Option<String>,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            timeout: 10,
+            notify: None,
+        }
+    }
+}
+
+impl ShutdownConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    // Including the first try, 1 disables retries.
+    pub max_attempts: u32,
+    // Doubled after each failed attempt, up to max_delay_ms.
+    pub initial_delay_ms: u64,
+    // Also the longest Retry-After we wait for, longer ones give up until the next poll.
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_delay_ms: 1000,
+            max_delay_ms: 15000,
+        }
+    }
+}
+
+impl RetryConfig {
+    // `section` is the name of the config section, for error messages.
+    fn validate(&self, section: &str) -> Result<()> {
+        if self.max_attempts == 0 {
+            bail!("{section}.max_attempts must be greater than 0");
+        }
+        if self.initial_delay_ms > self.max_delay_ms {
+            bail!("{section}.initial_delay_ms must not be greater than {section}.max_delay_ms");
+        }
+
+        Ok(())
+    }
+}
+
+impl Config {
+    // Loads the config file, then applies environment overrides and validates the result.
+    // When no path is given, TARKOV_STATUS_CONFIG is used, then `config.toml` if it exists.
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        let path = path.or_else(|| env::var_os(format!("{ENV_PREFIX}CONFIG")).map(PathBuf::from));
+
+        let mut config = match path {
+            Some(path) => Self::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Self::default(),
+        };
+
+        config.apply_env()?;
+
+        if let Some(url) = &config.webhook_url {
+            config
+                .destinations
+                .insert(0, DestinationConfig::from_webhook_url(url));
+        }
+        if config.translator.backend.is_none() && config.deepl_api_key.is_some() {
+            config.translator.backend = Some(TranslatorBackend::DeeplFree);
+            config.translator.api_key = config.deepl_api_key.clone();
+        }
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    fn apply_env(&mut self) -> Result<()> {
+        if let Some(value) = env_var("WEBHOOK_URL") {
+            self.webhook_url = Some(value);
+        }
+        if let Some(value) = env_var("DEEPL_API_KEY") {
+            self.deepl_api_key = Some(value);
+        }
+        if let Some(value) = env_var("POLL_INTERVAL") {
+            self.poll_interval = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}POLL_INTERVAL must be a number of seconds, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("STATUS_API_URL") {
+            self.status_api_url = value;
+        }
+        if let Some(value) = env_var("TARGET_LANG") {
+            self.target_lang = value;
+        }
+        if let Some(value) = env_var("TRANSLATOR_BACKEND") {
+            self.translator.backend = Some(value.parse()?);
+        }
+        if let Some(value) = env_var("TRANSLATOR_API_KEY") {
+            self.translator.api_key = Some(value);
+        }
+        if let Some(value) = env_var("TRANSLATOR_URL") {
+            self.translator.url = Some(value);
+        }
+        if let Some(value) = env_var("TRANSLATOR_CACHE_SIZE") {
+            self.translator.cache.size = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}TRANSLATOR_CACHE_SIZE must be a number, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("TRANSLATOR_CACHE_PATH") {
+            self.translator.cache.path = Some(PathBuf::from(value));
+        }
+        if let Some(value) = env_var("STATE_BACKEND") {
+            self.state.backend = value.parse()?;
+        }
+        if let Some(value) = env_var("STATE_PATH") {
+            self.state.path = Some(PathBuf::from(value));
+        }
+        if let Some(value) = env_var("STATE_FORGET_AFTER_POLLS") {
+            self.state.forget_after_polls = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}STATE_FORGET_AFTER_POLLS must be a number, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("STATE_FORGET_AFTER_MINUTES") {
+            self.state.forget_after_minutes = Some(value.parse().with_context(|| {
+                format!("{ENV_PREFIX}STATE_FORGET_AFTER_MINUTES must be a number of minutes, got \"{value}\"")
+            })?);
+        }
+        if let Some(value) = env_var("SERVICES_ENABLED") {
+            self.services.enabled = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}SERVICES_ENABLED must be true or false, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("MAINTENANCE_ENABLED") {
+            self.maintenance.enabled = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}MAINTENANCE_ENABLED must be true or false, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("MAINTENANCE_REMINDERS") {
+            self.maintenance.reminders = value
+                .split(',')
+                .filter(|minutes| !minutes.trim().is_empty())
+                .map(|minutes| minutes.trim().parse())
+                .collect::<Result<_, _>>()
+                .with_context(|| {
+                    format!("{ENV_PREFIX}MAINTENANCE_REMINDERS must be a comma separated list of minutes, got \"{value}\"")
+                })?;
+        }
+        if let Some(value) = env_var("MAINTENANCE_STARTED") {
+            self.maintenance.started = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}MAINTENANCE_STARTED must be true or false, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("MAINTENANCE_OVERRUN") {
+            self.maintenance.overrun = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}MAINTENANCE_OVERRUN must be true or false, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("MAINTENANCE_CHECK_INTERVAL") {
+            self.maintenance.check_interval = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}MAINTENANCE_CHECK_INTERVAL must be a number of seconds, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("SERVER_ENABLED") {
+            self.server.enabled = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}SERVER_ENABLED must be true or false, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("SERVER_LISTEN") {
+            self.server.listen = value;
+        }
+        if let Some(value) = env_var("SERVER_READY_THRESHOLD") {
+            self.server.ready_threshold = Some(value.parse().with_context(|| {
+                format!("{ENV_PREFIX}SERVER_READY_THRESHOLD must be a number of seconds, got \"{value}\"")
+            })?);
+        }
+        if let Some(value) = env_var("SHUTDOWN_TIMEOUT") {
+            self.shutdown.timeout = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}SHUTDOWN_TIMEOUT must be a number of seconds, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("SHUTDOWN_NOTIFY") {
+            self.shutdown.notify = Some(value);
+        }
+        if let Some(value) = env_var("RETRY_MAX_ATTEMPTS") {
+            self.retry.max_attempts = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}RETRY_MAX_ATTEMPTS must be a number, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("RETRY_INITIAL_DELAY_MS") {
+            self.retry.initial_delay_ms = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}RETRY_INITIAL_DELAY_MS must be a number of milliseconds, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("RETRY_MAX_DELAY_MS") {
+            self.retry.max_delay_ms = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}RETRY_MAX_DELAY_MS must be a number of milliseconds, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("DELIVERY_RETRY_MAX_ATTEMPTS") {
+            self.delivery_retry.max_attempts = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}DELIVERY_RETRY_MAX_ATTEMPTS must be a number, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("DELIVERY_RETRY_INITIAL_DELAY_MS") {
+            self.delivery_retry.initial_delay_ms = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}DELIVERY_RETRY_INITIAL_DELAY_MS must be a number of milliseconds, got \"{value}\"")
+            })?;
+        }
+        if let Some(value) = env_var("DELIVERY_RETRY_MAX_DELAY_MS") {
+            self.delivery_retry.max_delay_ms = value.parse().with_context(|| {
+                format!("{ENV_PREFIX}DELIVERY_RETRY_MAX_DELAY_MS must be a number of milliseconds, got \"{value}\"")
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.destinations.is_empty() {
+            bail!("No destination configured (add a [[destinations]] entry or set {ENV_PREFIX}WEBHOOK_URL)");
+        }
+        for (i, destination) in self.destinations.iter().enumerate() {
+            if self.destinations[..i]
+                .iter()
+                .any(|other| other.name == destination.name)
+            {
+                bail!("Duplicate destination name \"{}\"", destination.name);
+            }
+            destination.validate()?;
+
+            let locale = destination.locale(&self.target_lang);
+            if locale::get(locale).is_none() {
+                bail!(
+                    "Destination \"{}\": no locale for \"{locale}\", set `locale` to one of {}",
+                    destination.name,
+                    locale::codes().join(", ")
+                );
+            }
+        }
+        if !is_http_url(&self.status_api_url) {
+            bail!(
+                "status_api_url must be an http(s) URL, got \"{}\"",
+                self.status_api_url
+            );
+        }
+        if self.poll_interval == 0 {
+            bail!("poll_interval must be greater than 0");
+        }
+        if self.target_lang.is_empty() {
+            bail!("target_lang must not be empty");
+        }
+        self.translator.validate()?;
+        for (name, style) in self.event_styles.iter() {
+            if EventType::from_name(name).is_none() {
+                bail!(
+                    "event_styles: unknown event type \"{name}\", expected one of {} or a type code",
+                    EventType::names().join(", ")
+                );
+            }
+            if let Some(color) = style
+                .color
+                .as_ref()
+                .filter(|color| parse_color(color).is_none())
+            {
+                bail!("event_styles.{name}: color must look like \"#rrggbb\", got \"{color}\"");
+            }
+        }
+        if self.server.enabled {
+            self.server.listen()?;
+        }
+        if let Some(name) = &self.shutdown.notify {
+            if !self
+                .destinations
+                .iter()
+                .any(|destination| destination.name == *name)
+            {
+                bail!("shutdown.notify: no destination named \"{name}\"");
+            }
+        }
+        if self.maintenance.check_interval == 0 {
+            bail!("maintenance.check_interval must be greater than 0");
+        }
+        self.retry.validate("retry")?;
+        self.delivery_retry.validate("delivery_retry")?;
+        if self.state.backend == StateBackend::Sqlite && !cfg!(feature = "sqlite") {
+            bail!("The sqlite state backend requires building with the `sqlite` feature");
+        }
+        if self.state.forget_after_polls == 0 {
+            bail!("state.forget_after_polls must be greater than 0");
+        }
+
+        Ok(())
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval)
+    }
+
+    pub fn status_api_url(&self) -> &str {
+        self.status_api_url.trim_end_matches('/')
+    }
+
+    // Names were checked when loading the config.
+    pub fn event_styles(&self) -> HashMap<EventType, EventStyle> {
+        self.event_styles
+            .iter()
+            .filter_map(|(name, style)| Some((EventType::from_name(name)?, style.clone())))
+            .collect()
+    }
+}
+
+// Empty variables are treated as unset so an `ENV=` line in a compose file doesn't wipe the file value.
+fn env_var(name: &str) -> Option<String> {
+    env::var(format!("{ENV_PREFIX}{name}"))
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+// "#rrggbb" (the # being optional) to the number Discord expects.
+pub fn parse_color(color: &str) -> Option<u32> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    if hex.len() != 6 {
+        return None;
+    }
+
+    u32::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    // The environment is shared by every test of the process.
+    static ENV: Mutex<()> = Mutex::new(());
+
+    fn load(content: &str, vars: &[(&str, &str)]) -> Result<Config> {
+        let _lock = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, content).unwrap();
+
+        for (name, value) in vars {
+            env::set_var(format!("{ENV_PREFIX}{name}"), value);
+        }
+        let config = Config::load(Some(path));
+        for (name, _) in vars {
+            env::remove_var(format!("{ENV_PREFIX}{name}"));
+        }
+
+        config
+    }
+
+    fn error(content: &str) -> String {
+        load(content, &[]).unwrap_err().to_string()
+    }
+
+    const DESTINATION: &str = r#"
+        [[destinations]]
+        name = "discord"
+        url = "https://discord.com/api/webhooks/1/token"
+    "#;
+
+    #[test]
+    fn env_overrides_the_file() {
+        let content = format!(
+            r#"
+            poll_interval = 10
+            target_lang = "DE"
+
+            [state]
+            backend = "json"
+            forget_after_polls = 3
+
+            [maintenance]
+            reminders = [60]
+            {DESTINATION}
+            "#
+        );
+        let config = load(
+            &content,
+            &[
+                ("POLL_INTERVAL", "60"),
+                ("TARGET_LANG", ""),
+                ("STATE_BACKEND", "memory"),
+                ("STATE_FORGET_AFTER_MINUTES", "90"),
+                ("MAINTENANCE_REMINDERS", "30, 5,"),
+                ("DELIVERY_RETRY_MAX_ATTEMPTS", "2"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.poll_interval, 60);
+        // Empty variables leave the file value
+        assert_eq!(config.target_lang, "DE");
+        assert_eq!(config.state.backend, StateBackend::Memory);
+        assert_eq!(config.state.forget_after_polls, 3);
+        assert_eq!(config.state.forget_after_minutes, Some(90));
+        assert_eq!(config.maintenance.reminders, vec![30, 5]);
+        assert_eq!(config.delivery_retry.max_attempts, 2);
+    }
+
+    #[test]
+    fn invalid_env_values_are_rejected() {
+        let err = load(DESTINATION, &[("POLL_INTERVAL", "soon")]).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("TARKOV_STATUS_POLL_INTERVAL must be a number of seconds"));
+    }
+
+    #[test]
+    fn webhook_url_adds_a_default_destination() {
+        let config = load(
+            "",
+            &[("WEBHOOK_URL", "https://discord.com/api/webhooks/1/token")],
+        )
+        .unwrap();
+
+        assert_eq!(config.destinations.len(), 1);
+        let destination = &config.destinations[0];
+        assert_eq!(destination.name, "default");
+        assert_eq!(destination.kind, NotifierKind::Discord);
+        assert_eq!(
+            destination.url.as_deref(),
+            Some("https://discord.com/api/webhooks/1/token")
+        );
+    }
+
+    #[test]
+    fn webhook_url_comes_before_the_file_destinations() {
+        let content =
+            format!("webhook_url = \"https://discord.com/api/webhooks/2/token\"\n{DESTINATION}");
+        let config = load(&content, &[]).unwrap();
+
+        let names: Vec<_> = config.destinations.iter().map(|d| &d.name).collect();
+        assert_eq!(names, ["default", "discord"]);
+    }
+
+    #[test]
+    fn deepl_api_key_selects_deepl_free() {
+        let config = load(DESTINATION, &[("DEEPL_API_KEY", "secret:fx")]).unwrap();
+
+        assert_eq!(config.translator.backend(), TranslatorBackend::DeeplFree);
+        assert_eq!(config.translator.api_key.as_deref(), Some("secret:fx"));
+    }
+
+    #[test]
+    fn deepl_api_key_does_not_replace_a_configured_translator() {
+        let content = format!(
+            r#"
+            deepl_api_key = "secret:fx"
+
+            [translator]
+            backend = "libretranslate"
+            url = "http://localhost:5000"
+            {DESTINATION}
+            "#
+        );
+        let config = load(&content, &[]).unwrap();
+
+        assert_eq!(
+            config.translator.backend(),
+            TranslatorBackend::Libretranslate
+        );
+        assert_eq!(config.translator.api_key, None);
+    }
+
+    #[test]
+    fn destinations_are_required() {
+        assert!(error("").contains("No destination configured"));
+    }
+
+    #[test]
+    fn duplicate_destination_names_are_rejected() {
+        let content = format!("{DESTINATION}{DESTINATION}");
+
+        assert_eq!(error(&content), "Duplicate destination name \"discord\"");
+    }
+
+    #[test]
+    fn required_fields_depend_on_the_kind() {
+        let cases = [
+            ("discord", "", "missing url"),
+            ("slack", "", "missing url"),
+            ("json", "", "missing url"),
+            ("telegram", "", "missing bot_token"),
+            ("telegram", "bot_token = \"123:abc\"", "missing chat_id"),
+            (
+                "matrix",
+                "url = \"https://matrix.org\"",
+                "missing access_token",
+            ),
+            (
+                "matrix",
+                "url = \"https://matrix.org\"\naccess_token = \"token\"",
+                "missing room_id",
+            ),
+        ];
+
+        for (kind, fields, expected) in cases {
+            let content =
+                format!("[[destinations]]\nname = \"{kind}\"\nkind = \"{kind}\"\n{fields}\n");
+
+            assert_eq!(
+                error(&content),
+                format!("Destination \"{kind}\": {expected}"),
+                "{kind} with {fields:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn telegram_does_not_require_a_url() {
+        let content = r#"
+            [[destinations]]
+            name = "telegram"
+            kind = "telegram"
+            bot_token = "123:abc"
+            chat_id = "-100"
+        "#;
+
+        assert!(load(content, &[]).is_ok());
+    }
+
+    #[test]
+    fn destination_urls_must_be_http() {
+        let content = r#"
+            [[destinations]]
+            name = "discord"
+            url = "discord.com/api/webhooks/1/token"
+        "#;
+
+        assert!(error(content).contains("url must be an http(s) URL"));
+    }
+
+    #[test]
+    fn translators_require_their_credentials() {
+        let cases = [
+            (
+                "deepl-free",
+                "",
+                "The deepl-free translator requires an api_key",
+            ),
+            ("google", "", "The google translator requires an api_key"),
+            (
+                "libretranslate",
+                "",
+                "The libretranslate translator requires a url",
+            ),
+            (
+                "libretranslate",
+                "url = \"localhost:5000\"",
+                "translator url must be an http(s) URL, got \"localhost:5000\"",
+            ),
+        ];
+
+        for (backend, fields, expected) in cases {
+            let content =
+                format!("{DESTINATION}\n[translator]\nbackend = \"{backend}\"\n{fields}\n");
+
+            assert_eq!(error(&content), expected, "{backend} with {fields:?}");
+        }
+    }
+}
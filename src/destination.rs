@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    config::DestinationConfig,
+    locale::{self, Locale},
+    models::EventType,
+    notify::{
+        queue::{Delivery, DeliveryQueue},
+        Notification, Notifier,
+    },
+    retry::RetryPolicy,
+    template::Template,
+};
+
+pub struct Destination {
+    pub name: String,
+    pub language: String,
+    pub locale: &'static Locale,
+    event_types: Vec<EventType>,
+    // Takes service and global status changes.
+    pub services: bool,
+    template: Option<Template>,
+    notifier: Arc<dyn Notifier>,
+    queue: DeliveryQueue,
+}
+
+impl Destination {
+    pub fn new(
+        config: &DestinationConfig,
+        default_language: &str,
+        notifier: Box<dyn Notifier>,
+        retry_policy: RetryPolicy,
+    ) -> Result<Self> {
+        let locale_code = config.locale(default_language);
+        let locale = locale::get(locale_code).with_context(|| {
+            format!(
+                "Destination \"{}\": unknown locale \"{locale_code}\"",
+                config.name
+            )
+        })?;
+        let notifier: Arc<dyn Notifier> = notifier.into();
+
+        Ok(Self {
+            name: config.name.clone(),
+            language: config.language(default_language).to_string(),
+            locale,
+            // names were checked when loading the config
+            event_types: config
+                .event_types
+                .iter()
+                .filter_map(|name| EventType::from_name(name))
+                .collect(),
+            services: config.services.unwrap_or(true),
+            template: config
+                .template
+                .as_ref()
+                .map(Template::new)
+                .transpose()
+                .with_context(|| format!("Destination \"{}\"", config.name))?,
+            queue: DeliveryQueue::spawn(config.name.clone(), notifier.clone(), retry_policy),
+            notifier,
+        })
+    }
+
+    pub fn accepts(&self, event_type: EventType) -> bool {
+        self.event_types.is_empty() || self.event_types.contains(&event_type)
+    }
+
+    // Queues the notification behind the ones already waiting for this destination, see `DeliveryQueue`.
+    pub async fn deliver(&self, notification: &Notification, message: Option<String>) -> Delivery {
+        let notification = match &self.template {
+            Some(template) => template.render_or_default(notification, self.locale, &self.name),
+            None => notification.clone(),
+        };
+
+        self.queue.deliver(notification, message).await
+    }
+
+    // Sends right away, without queueing nor retrying, so any error (template included) reaches the caller.
+    pub async fn test(&self, notification: &Notification) -> Result<Option<String>> {
+        let notification = match &self.template {
+            Some(template) => template.render(notification, self.locale)?,
+            None => notification.clone(),
+        };
+
+        self.notifier.send(&notification).await
+    }
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+// Longest part of a response body we keep around for error messages.
+const SNIPPET_LEN: usize = 200;
+
+// Start of a response body, on a single line so it fits in a log entry.
+pub fn body_snippet(body: &str) -> String {
+    let body = body.trim();
+    let mut snippet: String = body
+        .chars()
+        .take(SNIPPET_LEN)
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+
+    if body.chars().count() > SNIPPET_LEN {
+        snippet.push('…');
+    }
+
+    snippet
+}
+
+// Delay asked for by a Retry-After header, given either in seconds or as an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means right away
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(retry_after: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(retry_after).unwrap());
+        headers
+    }
+
+    fn http_date(date: DateTime<Utc>) -> String {
+        date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+    }
+
+    #[test]
+    fn retry_after_in_seconds() {
+        assert_eq!(retry_after(&headers("120")), Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn retry_after_as_an_http_date() {
+        let date = http_date(Utc::now() + chrono::Duration::seconds(120));
+
+        let delay = retry_after(&headers(&date)).unwrap();
+
+        // The date is rounded down to the second
+        assert!(delay > Duration::from_secs(115) && delay <= Duration::from_secs(120));
+    }
+
+    #[test]
+    fn retry_after_in_the_past_means_right_away() {
+        let date = http_date(Utc::now() - chrono::Duration::seconds(120));
+
+        assert_eq!(retry_after(&headers(&date)), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn invalid_or_missing_retry_after_is_ignored() {
+        assert_eq!(retry_after(&headers("soon")), None);
+        assert_eq!(retry_after(&headers("-5")), None);
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn long_bodies_are_cut_on_a_single_line() {
+        let snippet = body_snippet(&format!("  error:\n{}", "x".repeat(300)));
+
+        assert!(snippet.starts_with("error: xxx"));
+        assert_eq!(snippet.chars().count(), SNIPPET_LEN + 1);
+        assert!(snippet.ends_with('…'));
+    }
+}
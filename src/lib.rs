@@ -0,0 +1,33 @@
+//! Watches status.escapefromtarkov.com and posts its events to chat services.
+//!
+//! [`Watcher`] reports the events that appeared or changed as a stream, for applications doing their
+//! own notifications. [`app::App`] is the whole pipeline run by the `tarkov-status` binary: translation,
+//! rendering, delivery to the configured destinations and saved state.
+
+pub mod app;
+pub mod config;
+pub mod destination;
+mod http;
+pub mod locale;
+pub mod maintenance;
+pub mod metrics;
+pub mod models;
+pub mod notify;
+pub mod render;
+pub mod retry;
+pub mod server;
+pub mod services;
+pub mod source;
+pub mod state;
+pub mod status;
+pub mod template;
+pub mod translate;
+pub mod watcher;
+
+pub use models::{Event, EventType};
+pub use source::StatusSource;
+pub use status::{StatusClient, StatusError};
+pub use watcher::{Change, ChangeKind, Watcher};
+
+#[macro_use]
+extern crate log;
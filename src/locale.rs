@@ -0,0 +1,257 @@
+use serde::Serialize;
+
+use crate::models::{EventType, ServiceState};
+
+// Every user facing string of a notification, event contents aside which go through the translator.
+#[derive(Serialize)]
+pub struct Locale {
+    pub code: &'static str,
+    pub update_installation: &'static str,
+    pub server_issues: &'static str,
+    pub maintenance: &'static str,
+    pub planned_work: &'static str,
+    pub announcement: &'static str,
+    pub unknown: &'static str,
+    pub since: &'static str,
+    pub resolved_since: &'static str,
+    pub status: &'static str,
+    pub resolved: &'static str,
+    pub offline: &'static str,
+    pub operational: &'static str,
+    pub degraded: &'static str,
+    pub updating: &'static str,
+    pub maintenance_upcoming: &'static str,
+    pub maintenance_started: &'static str,
+    pub maintenance_overrun: &'static str,
+    pub starts: &'static str,
+    pub expected_end: &'static str,
+    pub started: &'static str,
+    pub duration: &'static str,
+    pub updates: &'static str,
+    // Abbreviated units of durations.
+    pub hours: &'static str,
+    pub minutes: &'static str,
+    // Notice sent on shutdown, see `[shutdown] notify`.
+    pub going_offline: &'static str,
+    pub going_offline_description: &'static str,
+}
+
+impl Locale {
+    pub fn event_type(&self, event_type: EventType) -> &'static str {
+        match event_type {
+            EventType::UpdateInstallation => self.update_installation,
+            EventType::ServerIssues => self.server_issues,
+            EventType::Maintenance => self.maintenance,
+            EventType::PlannedWork => self.planned_work,
+            EventType::Announcement => self.announcement,
+            EventType::Resolved => self.resolved,
+            EventType::Other(_) => self.unknown,
+        }
+    }
+
+    pub fn service_state(&self, state: ServiceState) -> &'static str {
+        match state {
+            ServiceState::Operational => self.operational,
+            ServiceState::Updating => self.updating,
+            ServiceState::Degraded => self.degraded,
+            ServiceState::Down => self.offline,
+            ServiceState::Unknown => self.unknown,
+        }
+    }
+}
+
+pub static FR: Locale = Locale {
+    code: "fr",
+    update_installation: "Installation de mise à jour",
+    server_issues: "Problèmes de serveur",
+    maintenance: "Maintenance",
+    planned_work: "Travaux planifiés",
+    announcement: "Annonce",
+    unknown: "Inconnu",
+    since: "Depuis",
+    resolved_since: "Résolu depuis",
+    status: "Status",
+    resolved: "Résolu",
+    offline: "Hors ligne",
+    operational: "Opérationnel",
+    degraded: "Dégradé",
+    updating: "Mise à jour",
+    maintenance_upcoming: "Maintenance imminente",
+    maintenance_started: "Maintenance commencée",
+    maintenance_overrun: "Maintenance prolongée",
+    starts: "Début",
+    expected_end: "Fin prévue",
+    started: "Début",
+    duration: "Durée",
+    updates: "Mises à jour",
+    hours: "h",
+    minutes: "min",
+    going_offline: "Bot hors ligne",
+    going_offline_description: "Les événements ne seront plus publiés jusqu'à son redémarrage.",
+};
+
+pub static EN: Locale = Locale {
+    code: "en",
+    update_installation: "Update installation",
+    server_issues: "Server issues",
+    maintenance: "Maintenance",
+    planned_work: "Planned work",
+    announcement: "Announcement",
+    unknown: "Unknown",
+    since: "Since",
+    resolved_since: "Resolved since",
+    status: "Status",
+    resolved: "Resolved",
+    offline: "Offline",
+    operational: "Operational",
+    degraded: "Degraded",
+    updating: "Updating",
+    maintenance_upcoming: "Upcoming maintenance",
+    maintenance_started: "Maintenance started",
+    maintenance_overrun: "Maintenance overrun",
+    starts: "Starts",
+    expected_end: "Expected end",
+    started: "Started",
+    duration: "Duration",
+    updates: "Updates",
+    hours: "h",
+    minutes: "min",
+    going_offline: "Bot going offline",
+    going_offline_description: "Events won't be posted until it is back.",
+};
+
+pub static DE: Locale = Locale {
+    code: "de",
+    update_installation: "Update-Installation",
+    server_issues: "Serverprobleme",
+    maintenance: "Wartung",
+    planned_work: "Geplante Arbeiten",
+    announcement: "Ankündigung",
+    unknown: "Unbekannt",
+    since: "Seit",
+    resolved_since: "Behoben seit",
+    status: "Status",
+    resolved: "Behoben",
+    offline: "Offline",
+    operational: "Betriebsbereit",
+    degraded: "Beeinträchtigt",
+    updating: "Wird aktualisiert",
+    maintenance_upcoming: "Bevorstehende Wartung",
+    maintenance_started: "Wartung begonnen",
+    maintenance_overrun: "Wartung dauert länger",
+    starts: "Beginn",
+    expected_end: "Voraussichtliches Ende",
+    started: "Begonnen",
+    duration: "Dauer",
+    updates: "Aktualisierungen",
+    hours: "Std.",
+    minutes: "Min.",
+    going_offline: "Bot geht offline",
+    going_offline_description: "Ereignisse werden erst nach dem Neustart wieder veröffentlicht.",
+};
+
+pub static RU: Locale = Locale {
+    code: "ru",
+    update_installation: "Установка обновления",
+    server_issues: "Проблемы с серверами",
+    maintenance: "Техническое обслуживание",
+    planned_work: "Плановые работы",
+    announcement: "Объявление",
+    unknown: "Неизвестно",
+    since: "Начало",
+    resolved_since: "Решено",
+    status: "Статус",
+    resolved: "Решено",
+    offline: "Недоступно",
+    operational: "Работает",
+    degraded: "Нестабильно",
+    updating: "Обновление",
+    maintenance_upcoming: "Скоро техническое обслуживание",
+    maintenance_started: "Техническое обслуживание началось",
+    maintenance_overrun: "Техническое обслуживание затянулось",
+    starts: "Начало",
+    expected_end: "Ожидаемое окончание",
+    started: "Начало",
+    duration: "Длительность",
+    updates: "Обновления",
+    hours: "ч",
+    minutes: "мин",
+    going_offline: "Бот отключается",
+    going_offline_description: "События не будут публиковаться до его перезапуска.",
+};
+
+pub static ES: Locale = Locale {
+    code: "es",
+    update_installation: "Instalación de actualización",
+    server_issues: "Problemas de servidor",
+    maintenance: "Mantenimiento",
+    planned_work: "Trabajos programados",
+    announcement: "Anuncio",
+    unknown: "Desconocido",
+    since: "Desde",
+    resolved_since: "Resuelto desde",
+    status: "Estado",
+    resolved: "Resuelto",
+    offline: "Fuera de línea",
+    operational: "Operativo",
+    degraded: "Degradado",
+    updating: "Actualizando",
+    maintenance_upcoming: "Mantenimiento próximo",
+    maintenance_started: "Mantenimiento iniciado",
+    maintenance_overrun: "Mantenimiento prolongado",
+    starts: "Inicio",
+    expected_end: "Fin previsto",
+    started: "Inicio",
+    duration: "Duración",
+    updates: "Actualizaciones",
+    hours: "h",
+    minutes: "min",
+    going_offline: "El bot se desconecta",
+    going_offline_description: "No se publicarán eventos hasta que vuelva.",
+};
+
+pub static PL: Locale = Locale {
+    code: "pl",
+    update_installation: "Instalacja aktualizacji",
+    server_issues: "Problemy z serwerami",
+    maintenance: "Konserwacja",
+    planned_work: "Planowane prace",
+    announcement: "Ogłoszenie",
+    unknown: "Nieznany",
+    since: "Od",
+    resolved_since: "Rozwiązano",
+    status: "Status",
+    resolved: "Rozwiązano",
+    offline: "Offline",
+    operational: "Działa",
+    degraded: "Problemy",
+    updating: "Aktualizacja",
+    maintenance_upcoming: "Nadchodząca konserwacja",
+    maintenance_started: "Konserwacja rozpoczęta",
+    maintenance_overrun: "Konserwacja przedłużona",
+    starts: "Początek",
+    expected_end: "Przewidywany koniec",
+    started: "Początek",
+    duration: "Czas trwania",
+    updates: "Aktualizacje",
+    hours: "godz.",
+    minutes: "min",
+    going_offline: "Bot przechodzi w tryb offline",
+    going_offline_description: "Zdarzenia nie będą publikowane do czasu jego powrotu.",
+};
+
+static LOCALES: [&Locale; 6] = [&FR, &EN, &DE, &RU, &ES, &PL];
+
+// Accepts translator style codes too, "EN-GB" gives the english locale.
+pub fn get(code: &str) -> Option<&'static Locale> {
+    let code = code.split(['-', '_']).next().unwrap_or(code);
+
+    LOCALES
+        .iter()
+        .copied()
+        .find(|locale| locale.code.eq_ignore_ascii_case(code))
+}
+
+pub fn codes() -> Vec<&'static str> {
+    LOCALES.iter().map(|locale| locale.code).collect()
+}
@@ -0,0 +1,437 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use futures::future::join_all;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{EventStyle, MaintenanceConfig},
+    destination::Destination,
+    models::{Event, EventType},
+    render,
+    state::SavedEvents,
+    translate::{translate_all, Translator},
+};
+
+// Planned maintenance window, and which follow-ups were already sent about it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    // Minutes before the start of the reminders already sent (or skipped).
+    #[serde(default)]
+    pub reminders_sent: Vec<u64>,
+    #[serde(default)]
+    pub started_sent: bool,
+    #[serde(default)]
+    pub overrun_sent: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reminder {
+    Upcoming,
+    Started,
+    Overrun,
+}
+
+impl Schedule {
+    fn new(
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        config: &MaintenanceConfig,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut schedule = Self {
+            start,
+            end,
+            reminders_sent: Vec::new(),
+            started_sent: now >= start,
+            // A window already over when announced is only being reported on, not overrunning
+            overrun_sent: end.is_some_and(|end| now >= end),
+        };
+        // Reminders that are already late when we hear about the window would only repeat the announcement
+        schedule.reminders_sent = schedule.passed_reminders(config, now);
+
+        schedule
+    }
+
+    fn passed_reminders(&self, config: &MaintenanceConfig, now: DateTime<Utc>) -> Vec<u64> {
+        config
+            .reminders
+            .iter()
+            .copied()
+            .filter(|minutes| now >= self.start - Duration::minutes(*minutes as i64))
+            .collect()
+    }
+
+    // The follow-up to send now if any, marking it (and the ones it makes pointless) as sent.
+    // Only the latest stage is reported when several became due since the last check.
+    pub fn due(&mut self, config: &MaintenanceConfig, now: DateTime<Utc>) -> Option<Reminder> {
+        if let Some(end) = self.end.filter(|end| now >= *end) {
+            if self.overrun_sent {
+                return None;
+            }
+            debug!("Maintenance planned to end at {end} is still ongoing");
+            self.overrun_sent = true;
+            self.started_sent = true;
+            return config.overrun.then_some(Reminder::Overrun);
+        }
+
+        if now >= self.start {
+            if self.started_sent {
+                return None;
+            }
+            self.started_sent = true;
+            self.reminders_sent = config.reminders.clone();
+            return config.started.then_some(Reminder::Started);
+        }
+
+        let passed = self.passed_reminders(config, now);
+        if passed
+            .iter()
+            .all(|minutes| self.reminders_sent.contains(minutes))
+        {
+            return None;
+        }
+        self.reminders_sent = passed;
+
+        Some(Reminder::Upcoming)
+    }
+}
+
+// Window announced by an event, from its planned start/end fields or else from the (english) content
+// of maintenance and planned work announcements: times in other messages say when something happened.
+// A schedule that didn't move keeps track of the follow-ups already sent.
+pub fn schedule(
+    event: &Event,
+    previous: Option<&Schedule>,
+    config: &MaintenanceConfig,
+    now: DateTime<Utc>,
+) -> Option<Schedule> {
+    if !config.enabled || event.solve_time.is_some() {
+        return None;
+    }
+
+    let (start, end) = match event.start_time {
+        Some(start) => (start, event.end_time),
+        None if matches!(
+            event.event_type,
+            EventType::Maintenance | EventType::PlannedWork
+        ) =>
+        {
+            parse_window(&event.content, event.time)?
+        }
+        None => return None,
+    };
+
+    match previous {
+        Some(previous) if previous.start == start && previous.end == end => Some(previous.clone()),
+        _ => Some(Schedule::new(start, end, config, now)),
+    }
+}
+
+fn range_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?i)\b(\d{1,2}):(\d{2})\s*(?:-|–|—|to|till|until)\s*(\d{1,2}):(\d{2})\s*(UTC|GMT|MSK|CEST|CET)?\b").unwrap()
+    })
+}
+
+fn start_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:at|from|starting)\s+(\d{1,2}):(\d{2})\s*(UTC|GMT|MSK|CEST|CET)\b")
+            .unwrap()
+    })
+}
+
+fn duration_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:downtime|duration|last|take)\D{0,40}?(\d+(?:[.,]\d+)?)\s*(hours?|hrs?|h|minutes?|mins?)\b")
+            .unwrap()
+    })
+}
+
+// Understands "from 10:00 to 14:00 UTC", "at 09:00 UTC (12:00 MSK)" and "estimated downtime is 3 hours".
+// Messages rarely give a date, the time is taken as the next occurrence around the time the message was posted.
+pub fn parse_window(
+    content: &str,
+    posted: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+    if let Some(captures) = range_regex().captures(content) {
+        let zone = captures.get(5).map(|zone| zone.as_str());
+        let start = resolve_time(&captures, 1, zone, posted)?;
+        let mut end = resolve_time(&captures, 3, zone, start)?;
+        if end < start {
+            end += Duration::days(1);
+        }
+
+        return Some((start, Some(end)));
+    }
+
+    let captures = start_regex().captures(content)?;
+    let start = resolve_time(
+        &captures,
+        1,
+        captures.get(3).map(|zone| zone.as_str()),
+        posted,
+    )?;
+    let end = parse_duration(content).map(|duration| start + duration);
+
+    Some((start, end))
+}
+
+fn parse_duration(content: &str) -> Option<Duration> {
+    let captures = duration_regex().captures(content)?;
+    let amount: f64 = captures[1].replace(',', ".").parse().ok()?;
+    let minutes = if captures[2].to_lowercase().starts_with('h') {
+        amount * 60.0
+    } else {
+        amount
+    };
+
+    Some(Duration::minutes(minutes.round() as i64))
+}
+
+// Hours and minutes at `index` and `index + 1` in the zone, on the day of `around`
+// or the next one when that would be more than 12 hours in the past.
+fn resolve_time(
+    captures: &Captures,
+    index: usize,
+    zone: Option<&str>,
+    around: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let time = NaiveTime::from_hms_opt(
+        captures[index].parse().ok()?,
+        captures[index + 1].parse().ok()?,
+        0,
+    )?;
+    let offset = Duration::hours(utc_offset(zone.unwrap_or("UTC")));
+
+    let mut resolved = around.date_naive().and_time(time).and_utc() - offset;
+    if resolved < around - Duration::hours(12) {
+        resolved += Duration::days(1);
+    }
+
+    Some(resolved)
+}
+
+// Hours to add to UTC, for the zones BSG uses in its announcements.
+fn utc_offset(zone: &str) -> i64 {
+    match zone.to_uppercase().as_str() {
+        "MSK" => 3,
+        "CEST" => 2,
+        "CET" => 1,
+        _ => 0,
+    }
+}
+
+// Sends the reminders that became due, returns whether any schedule changed.
+pub async fn remind(
+    saved_events: &mut SavedEvents,
+    config: &MaintenanceConfig,
+    destinations: &[Destination],
+    translator: &dyn Translator,
+    event_styles: &HashMap<EventType, EventStyle>,
+    now: DateTime<Utc>,
+) -> bool {
+    let mut changed = false;
+
+    for (id, saved_event) in saved_events.iter_mut() {
+        if saved_event.solve_time.is_some() {
+            continue;
+        }
+        let Some(schedule) = saved_event.maintenance.as_mut() else {
+            continue;
+        };
+
+        let before = schedule.clone();
+        let reminder = schedule.due(config, now);
+        changed |= *schedule != before;
+
+        let Some(reminder) = reminder else {
+            continue;
+        };
+        info!("Sending {reminder:?} maintenance reminder for event {id}");
+
+        let targets: Vec<&Destination> = destinations
+            .iter()
+            .filter(|destination| destination.accepts(saved_event.event_type))
+            .collect();
+        let translations = translate_all(
+            translator,
+            &saved_event.content,
+            targets
+                .iter()
+                .map(|destination| destination.language.as_str()),
+        )
+        .await;
+
+        let schedule = &*schedule;
+        let deliveries = targets.iter().map(|destination| {
+            let notification = render::maintenance(
+                reminder,
+                schedule,
+                &translations[&destination.language],
+                destination.locale,
+                event_styles.get(&saved_event.event_type),
+            );
+            async move { destination.deliver(&notification, None).await }
+        });
+        join_all(deliveries).await;
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap()
+    }
+
+    fn event(event_type: EventType, content: &str, posted: DateTime<Utc>) -> Event {
+        Event {
+            id: "event".to_string(),
+            content: content.to_string(),
+            event_type,
+            time: posted,
+            solve_time: None,
+            start_time: None,
+            end_time: None,
+        }
+    }
+
+    #[test]
+    fn parses_a_range() {
+        assert_eq!(
+            parse_window("Maintenance from 10:00 to 14:00 UTC", at(1, 8, 0)),
+            Some((at(1, 10, 0), Some(at(1, 14, 0))))
+        );
+    }
+
+    #[test]
+    fn parses_a_range_in_another_zone() {
+        assert_eq!(
+            parse_window("Works from 12:00 - 15:00 MSK", at(1, 8, 0)),
+            Some((at(1, 9, 0), Some(at(1, 12, 0))))
+        );
+    }
+
+    #[test]
+    fn parses_a_range_over_midnight() {
+        assert_eq!(
+            parse_window("from 23:00 to 02:00 UTC", at(1, 20, 0)),
+            Some((at(1, 23, 0), Some(at(2, 2, 0))))
+        );
+    }
+
+    #[test]
+    fn parses_a_start_and_a_duration() {
+        assert_eq!(
+            parse_window(
+                "Servers will be shut down at 09:00 UTC, estimated downtime is 3 hours",
+                at(1, 8, 0)
+            ),
+            Some((at(1, 9, 0), Some(at(1, 12, 0))))
+        );
+        assert_eq!(
+            parse_window("Starting 09:00 UTC", at(1, 8, 0)),
+            Some((at(1, 9, 0), None))
+        );
+    }
+
+    #[test]
+    fn times_long_past_are_the_next_day() {
+        assert_eq!(
+            parse_window("Maintenance at 09:00 UTC", at(1, 23, 0)),
+            Some((at(2, 9, 0), None))
+        );
+    }
+
+    #[test]
+    fn ignores_messages_without_times() {
+        assert_eq!(
+            parse_window("Servers are being restarted", at(1, 8, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn follows_up_on_each_stage_once() {
+        let config = MaintenanceConfig::default();
+        let mut schedule = Schedule::new(at(1, 10, 0), Some(at(1, 12, 0)), &config, at(1, 8, 0));
+
+        assert_eq!(schedule.due(&config, at(1, 8, 30)), None);
+        assert_eq!(schedule.due(&config, at(1, 9, 5)), Some(Reminder::Upcoming));
+        assert_eq!(schedule.due(&config, at(1, 9, 6)), None);
+        assert_eq!(
+            schedule.due(&config, at(1, 9, 55)),
+            Some(Reminder::Upcoming)
+        );
+        assert_eq!(schedule.due(&config, at(1, 10, 1)), Some(Reminder::Started));
+        assert_eq!(schedule.due(&config, at(1, 11, 0)), None);
+        assert_eq!(schedule.due(&config, at(1, 12, 1)), Some(Reminder::Overrun));
+        assert_eq!(schedule.due(&config, at(1, 13, 0)), None);
+    }
+
+    #[test]
+    fn only_reports_the_latest_stage() {
+        let config = MaintenanceConfig::default();
+        let mut schedule = Schedule::new(at(1, 10, 0), Some(at(1, 12, 0)), &config, at(1, 8, 0));
+
+        assert_eq!(
+            schedule.due(&config, at(1, 10, 30)),
+            Some(Reminder::Started)
+        );
+        assert_eq!(schedule.due(&config, at(1, 10, 31)), None);
+    }
+
+    #[test]
+    fn skips_reminders_already_late_when_announced() {
+        let config = MaintenanceConfig::default();
+        let mut schedule = Schedule::new(at(1, 10, 0), None, &config, at(1, 9, 30));
+
+        assert_eq!(schedule.due(&config, at(1, 9, 31)), None);
+        assert_eq!(
+            schedule.due(&config, at(1, 9, 51)),
+            Some(Reminder::Upcoming)
+        );
+    }
+
+    #[test]
+    fn windows_over_when_announced_stay_quiet() {
+        let config = MaintenanceConfig::default();
+        let event = event(
+            EventType::Maintenance,
+            "Maintenance from 12:00 to 13:00 UTC is over",
+            at(1, 15, 0),
+        );
+
+        let mut schedule = schedule(&event, None, &config, at(1, 15, 0)).unwrap();
+        assert_eq!(schedule.due(&config, at(1, 15, 1)), None);
+    }
+
+    #[test]
+    fn only_maintenance_content_is_parsed() {
+        let config = MaintenanceConfig::default();
+        let issue = event(
+            EventType::ServerIssues,
+            "From 12:00 to 13:00 UTC players had login issues",
+            at(1, 15, 0),
+        );
+        assert_eq!(schedule(&issue, None, &config, at(1, 15, 0)), None);
+
+        let planned = Event {
+            start_time: Some(at(1, 18, 0)),
+            ..issue
+        };
+        assert!(schedule(&planned, None, &config, at(1, 15, 0)).is_some());
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::OnceLock;
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+pub struct Metrics {
+    registry: Registry,
+    // endpoint, outcome (success, request, status or parse)
+    pub status_requests: IntCounterVec,
+    // endpoint
+    pub status_request_duration: HistogramVec,
+    // event_type, kind (new, changed or resolved)
+    pub events: IntCounterVec,
+    // event_type, as of the last successful poll
+    pub events_listed: IntGaugeVec,
+    // backend, outcome (success or failure)
+    pub translations: IntCounterVec,
+    // backend
+    pub translated_characters: IntCounterVec,
+    // destination, status (HTTP status code, "ok" or "error" when there is none)
+    pub deliveries: IntCounterVec,
+    // destination
+    pub delivery_duration: HistogramVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("tarkov_status".to_string()), None).unwrap();
+
+        let metrics = Self {
+            status_requests: IntCounterVec::new(
+                Opts::new("status_requests_total", "Requests to the status API"),
+                &["endpoint", "outcome"],
+            )
+            .unwrap(),
+            status_request_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "status_request_duration_seconds",
+                    "Latency of the status API",
+                ),
+                &["endpoint"],
+            )
+            .unwrap(),
+            events: IntCounterVec::new(
+                Opts::new("events_total", "Events posted, by what happened to them"),
+                &["event_type", "kind"],
+            )
+            .unwrap(),
+            events_listed: IntGaugeVec::new(
+                Opts::new(
+                    "events_listed",
+                    "Events currently listed on the status page",
+                ),
+                &["event_type"],
+            )
+            .unwrap(),
+            translations: IntCounterVec::new(
+                Opts::new("translations_total", "Calls to the translation API"),
+                &["backend", "outcome"],
+            )
+            .unwrap(),
+            translated_characters: IntCounterVec::new(
+                Opts::new(
+                    "translated_characters_total",
+                    "Characters sent to the translation API",
+                ),
+                &["backend"],
+            )
+            .unwrap(),
+            deliveries: IntCounterVec::new(
+                Opts::new("deliveries_total", "Delivery attempts to destinations"),
+                &["destination", "status"],
+            )
+            .unwrap(),
+            delivery_duration: HistogramVec::new(
+                HistogramOpts::new("delivery_duration_seconds", "Latency of delivery attempts"),
+                &["destination"],
+            )
+            .unwrap(),
+            registry,
+        };
+
+        let collectors: [Box<dyn prometheus::core::Collector>; 8] = [
+            Box::new(metrics.status_requests.clone()),
+            Box::new(metrics.status_request_duration.clone()),
+            Box::new(metrics.events.clone()),
+            Box::new(metrics.events_listed.clone()),
+            Box::new(metrics.translations.clone()),
+            Box::new(metrics.translated_characters.clone()),
+            Box::new(metrics.deliveries.clone()),
+            Box::new(metrics.delivery_duration.clone()),
+        ];
+        for collector in collectors {
+            metrics.registry.register(collector).unwrap();
+        }
+
+        metrics
+    }
+}
+
+pub fn get() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+// Text exposition format, served on /metrics.
+pub fn encode() -> String {
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&get().registry.gather(), &mut buffer) {
+        error!("Failed to encode metrics: {e}");
+    }
+
+    String::from_utf8(buffer).unwrap_or_default()
+}
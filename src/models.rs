@@ -0,0 +1,224 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+// Type codes used by the status API. Codes we don't know about yet are kept as is in `Other`.
+// 1 and 2 are the codes the bot has always handled. BSG doesn't document the list and no captured response
+// confirms 3 to 6 yet, so a destination can still filter or style a type by its raw code (e.g. "7").
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(from = "u32", into = "u32")]
+pub enum EventType {
+    UpdateInstallation,
+    ServerIssues,
+    Maintenance,
+    PlannedWork,
+    Announcement,
+    Resolved,
+    Other(u32),
+}
+
+impl Default for EventType {
+    fn default() -> Self {
+        EventType::Other(0)
+    }
+}
+
+impl From<u32> for EventType {
+    fn from(code: u32) -> Self {
+        match code {
+            1 => EventType::UpdateInstallation,
+            2 => EventType::ServerIssues,
+            3 => EventType::Maintenance,
+            4 => EventType::PlannedWork,
+            5 => EventType::Announcement,
+            6 => EventType::Resolved,
+            code => EventType::Other(code),
+        }
+    }
+}
+
+impl From<EventType> for u32 {
+    fn from(event_type: EventType) -> Self {
+        match event_type {
+            EventType::UpdateInstallation => 1,
+            EventType::ServerIssues => 2,
+            EventType::Maintenance => 3,
+            EventType::PlannedWork => 4,
+            EventType::Announcement => 5,
+            EventType::Resolved => 6,
+            EventType::Other(code) => code,
+        }
+    }
+}
+
+impl EventType {
+    const KNOWN: [EventType; 6] = [
+        EventType::UpdateInstallation,
+        EventType::ServerIssues,
+        EventType::Maintenance,
+        EventType::PlannedWork,
+        EventType::Announcement,
+        EventType::Resolved,
+    ];
+
+    // Name used to refer to the type in the config file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventType::UpdateInstallation => "UpdateInstallation",
+            EventType::ServerIssues => "ServerIssues",
+            EventType::Maintenance => "Maintenance",
+            EventType::PlannedWork => "PlannedWork",
+            EventType::Announcement => "Announcement",
+            EventType::Resolved => "Resolved",
+            EventType::Other(_) => "Other",
+        }
+    }
+
+    // Accepts the names above as well as raw codes, e.g. "7" for a type not modeled yet.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Ok(code) = name.parse::<u32>() {
+            return Some(EventType::from(code));
+        }
+
+        EventType::KNOWN
+            .into_iter()
+            .find(|event_type| event_type.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn names() -> Vec<&'static str> {
+        EventType::KNOWN
+            .iter()
+            .map(|event_type| event_type.name())
+            .collect()
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventType::Other(code) => write!(f, "Other({code})"),
+            event_type => write!(f, "{}", event_type.name()),
+        }
+    }
+}
+
+// Health of a service as reported by the status page, also used for the global status.
+#[derive(Serialize_repr, Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ServiceState {
+    Operational = 0,
+    Updating = 1,
+    Degraded = 2,
+    Down = 3,
+    #[serde(other)]
+    Unknown = 255,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ServiceStatus {
+    pub name: String,
+    pub status: ServiceState,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GlobalStatus {
+    pub status: ServiceState,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Event {
+    #[serde(alias = "_id")]
+    pub id: String,
+    pub content: String,
+    #[serde(alias = "type")]
+    pub event_type: EventType,
+    pub time: DateTime<Utc>,
+    #[serde(alias = "solveTime")]
+    pub solve_time: Option<DateTime<Utc>>,
+    // Planned window, only given by some maintenance announcements.
+    #[serde(default, alias = "startTime")]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(default, alias = "endTime")]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn deserializes_a_message_list() {
+        // Shaped like an /api/message/list response
+        let list = json!([
+            {
+                "_id": "65a1",
+                "content": "Servers are down",
+                "type": 2,
+                "time": "2024-01-01T10:00:00.000Z",
+                "solveTime": "2024-01-01T11:30:00.000Z",
+            },
+            {
+                "_id": "65a2",
+                "content": "Update installation",
+                "type": 1,
+                "time": "2024-01-02T08:00:00.000Z",
+                "solveTime": null,
+                "startTime": "2024-01-02T09:00:00.000Z",
+                "endTime": "2024-01-02T12:00:00.000Z",
+            },
+            {
+                "_id": "65a3",
+                "content": "Something new",
+                "type": 42,
+                "time": "2024-01-03T08:00:00.000Z",
+                "solveTime": null,
+            },
+        ]);
+
+        let events: Vec<Event> = serde_json::from_value(list).unwrap();
+
+        assert_eq!(events[0].id, "65a1");
+        assert_eq!(events[0].event_type, EventType::ServerIssues);
+        assert_eq!(
+            events[0].solve_time,
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 11, 30, 0).unwrap())
+        );
+        assert_eq!(events[0].start_time, None);
+        assert_eq!(events[1].event_type, EventType::UpdateInstallation);
+        assert_eq!(
+            events[1].end_time,
+            Some(Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap())
+        );
+        assert_eq!(events[2].event_type, EventType::Other(42));
+    }
+
+    #[test]
+    fn type_codes_round_trip() {
+        for code in 0..10 {
+            let event_type: EventType = serde_json::from_value(json!(code)).unwrap();
+
+            assert_eq!(serde_json::to_value(event_type).unwrap(), json!(code));
+        }
+        assert_eq!(EventType::from(42), EventType::Other(42));
+        assert_eq!(u32::from(EventType::Other(42)), 42);
+        assert_eq!(EventType::Other(42).to_string(), "Other(42)");
+    }
+
+    #[test]
+    fn types_are_named_or_given_by_code() {
+        assert_eq!(
+            EventType::from_name("serverissues"),
+            Some(EventType::ServerIssues)
+        );
+        assert_eq!(EventType::from_name("2"), Some(EventType::ServerIssues));
+        assert_eq!(EventType::from_name("42"), Some(EventType::Other(42)));
+        assert_eq!(EventType::from_name("Other"), None);
+    }
+}
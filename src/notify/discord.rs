@@ -0,0 +1,349 @@
+use std::{sync::Mutex, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::{header::HeaderMap, RequestBuilder, Response, StatusCode, Url};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::time::{self, Instant};
+use webhook::models::Message;
+
+use super::{error_for_status, FieldValue, Notification, Notifier};
+
+const DEFAULT_USERNAME: &str = "Escape from Tarkov Status";
+
+// Only these fields are accepted when editing a webhook message.
+const EDITABLE_FIELDS: [&str; 4] = ["content", "embeds", "allowed_mentions", "components"];
+
+#[derive(Deserialize)]
+struct SentMessage {
+    id: String,
+}
+
+pub struct DiscordNotifier {
+    client: reqwest::Client,
+    url: Url,
+    username: String,
+    avatar_url: Option<String>,
+    // Set when the last response said the webhook's rate limit bucket is empty.
+    blocked_until: Mutex<Option<Instant>>,
+}
+
+impl DiscordNotifier {
+    pub fn new(
+        client: reqwest::Client,
+        url: &str,
+        username: Option<String>,
+        avatar_url: Option<String>,
+    ) -> Result<Self> {
+        let url = Url::parse(url).with_context(|| format!("Invalid webhook URL {url}"))?;
+
+        Ok(Self {
+            client,
+            url,
+            username: username.unwrap_or_else(|| DEFAULT_USERNAME.to_string()),
+            avatar_url,
+            blocked_until: Mutex::new(None),
+        })
+    }
+
+    // Sends the request once the bucket has room for it, keeping track of what's left afterwards.
+    // Running out anyway (e.g. the webhook is shared with another app) ends in a 429 with a retry_after.
+    async fn execute(&self, request: RequestBuilder) -> Result<Response> {
+        let blocked_until = *self.blocked_until.lock().unwrap();
+        if let Some(blocked_until) = blocked_until {
+            debug!("Discord rate limit reached, waiting for the bucket to reset");
+            time::sleep_until(blocked_until).await;
+        }
+
+        let resp = request.send().await?;
+        *self.blocked_until.lock().unwrap() = bucket_reset(resp.headers());
+
+        Ok(resp)
+    }
+
+    fn build_message(&self, notification: &Notification) -> Message {
+        let mut message = Message::new();
+
+        message.username(&self.username);
+        if let Some(avatar_url) = &self.avatar_url {
+            message.avatar_url(avatar_url);
+        }
+
+        message.embed(|embed| {
+            embed
+                .title(&notification.title)
+                .description(&notification.description)
+                .color(&notification.color().to_string());
+
+            if let Some(thumbnail) = &notification.thumbnail {
+                embed.thumbnail(thumbnail);
+            }
+            if let Some(url) = &notification.url {
+                embed.url(url);
+            }
+
+            for field in notification.fields.iter() {
+                let value = match &field.value {
+                    FieldValue::Time(time) => format!("<t:{}:R>", time.timestamp()),
+                    value => value.to_text(),
+                };
+                embed.field(&field.name, &value, field.inline);
+            }
+
+            embed
+        });
+
+        message
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    // Executes the webhook with `wait=true`, so Discord answers with the created message and we get its id back.
+    async fn send(&self, notification: &Notification) -> Result<Option<String>> {
+        let mut url = self.url.clone();
+        url.query_pairs_mut().append_pair("wait", "true");
+
+        let resp = self
+            .execute(
+                self.client
+                    .post(url)
+                    .json(&self.build_message(notification)),
+            )
+            .await?;
+        let sent: SentMessage = error_for_status(resp).await?.json().await?;
+
+        Ok(Some(sent.id))
+    }
+
+    async fn update(&self, message_id: &str, notification: &Notification) -> Result<bool> {
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid webhook URL {}", self.url))?
+            .pop_if_empty()
+            .push("messages")
+            .push(message_id);
+
+        let mut body = serde_json::to_value(self.build_message(notification))?;
+        if let Value::Object(fields) = &mut body {
+            fields.retain(|name, _| EDITABLE_FIELDS.contains(&name.as_str()));
+        }
+
+        let resp = self.execute(self.client.patch(url).json(&body)).await?;
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        error_for_status(resp).await?;
+
+        Ok(true)
+    }
+}
+
+// When the bucket is empty, the moment it refills according to the X-RateLimit-* headers.
+fn bucket_reset(headers: &HeaderMap) -> Option<Instant> {
+    let header = |name: &str| -> Option<f64> { headers.get(name)?.to_str().ok()?.parse().ok() };
+
+    let remaining = header("x-ratelimit-remaining")?;
+    let reset_after = Duration::try_from_secs_f64(header("x-ratelimit-reset-after")?).ok()?;
+
+    (remaining < 1.0).then(|| Instant::now() + reset_after)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::future::join_all;
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, Request, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::{locale, notify::queue::DeliveryQueue, render, retry::RetryPolicy};
+
+    const WEBHOOK_PATH: &str = "/api/webhooks/1/token";
+
+    fn sent(id: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({ "id": id, "channel_id": "7" }))
+    }
+
+    // Backoff delays of a few ms, so waiting longer than that can only come from Discord's answers.
+    fn queue(server: &MockServer) -> DeliveryQueue {
+        let notifier = DiscordNotifier::new(
+            reqwest::Client::new(),
+            &format!("{}{WEBHOOK_PATH}", server.uri()),
+            None,
+            None,
+        )
+        .unwrap();
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(1),
+        };
+
+        DeliveryQueue::spawn("discord".to_string(), Arc::new(notifier), policy)
+    }
+
+    fn notification(description: &str) -> Notification {
+        Notification {
+            description: description.to_string(),
+            ..render::sample(&locale::EN)
+        }
+    }
+
+    async fn posts(server: &MockServer) -> Vec<Request> {
+        server.received_requests().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_retry_after_of_a_429() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(WEBHOOK_PATH))
+            .respond_with(ResponseTemplate::new(429).set_body_json(json!({
+                "message": "You are being rate limited.",
+                "retry_after": 0.3,
+                "global": false
+            })))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(WEBHOOK_PATH))
+            .respond_with(sent("1001"))
+            .mount(&server)
+            .await;
+
+        let start = Instant::now();
+        let delivery = queue(&server)
+            .deliver(notification("Servers are down"), None)
+            .await;
+
+        assert!(delivery.ok);
+        assert_eq!(delivery.message.as_deref(), Some("1001"));
+        assert_eq!(posts(&server).await.len(), 2);
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_the_retry_after_is_too_long() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(WEBHOOK_PATH))
+            .respond_with(ResponseTemplate::new(429).set_body_json(json!({ "retry_after": 60.0 })))
+            .mount(&server)
+            .await;
+
+        let delivery = queue(&server)
+            .deliver(notification("Servers are down"), None)
+            .await;
+
+        assert!(!delivery.ok);
+        assert_eq!(posts(&server).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_a_rejected_message() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(WEBHOOK_PATH))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "message": "Invalid Form Body",
+                "code": 50035
+            })))
+            .mount(&server)
+            .await;
+
+        let delivery = queue(&server)
+            .deliver(notification("Servers are down"), None)
+            .await;
+
+        assert!(!delivery.ok);
+        assert_eq!(posts(&server).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn waits_for_an_empty_bucket_to_reset() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(WEBHOOK_PATH))
+            .respond_with(
+                sent("1001")
+                    .insert_header("x-ratelimit-remaining", "0")
+                    .insert_header("x-ratelimit-reset-after", "0.3"),
+            )
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(WEBHOOK_PATH))
+            .respond_with(sent("1002"))
+            .mount(&server)
+            .await;
+
+        let queue = queue(&server);
+        let start = Instant::now();
+        let first = queue.deliver(notification("first"), None).await;
+        let second = queue.deliver(notification("second"), None).await;
+
+        assert!(first.ok && second.ok);
+        // No 429 on the way, the second message waited for the bucket instead
+        assert_eq!(posts(&server).await.len(), 2);
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn delivers_in_the_order_queued() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(WEBHOOK_PATH))
+            .respond_with(sent("1001"))
+            .mount(&server)
+            .await;
+
+        let queue = queue(&server);
+        let descriptions: Vec<String> = (1..=5).map(|i| format!("Event {i}")).collect();
+        let deliveries = join_all(
+            descriptions
+                .iter()
+                .map(|description| queue.deliver(notification(description), None)),
+        )
+        .await;
+
+        assert!(deliveries.iter().all(|delivery| delivery.ok));
+        let posted: Vec<String> = posts(&server)
+            .await
+            .iter()
+            .map(|request| {
+                let body: Value = serde_json::from_slice(&request.body).unwrap();
+                body["embeds"][0]["description"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(posted, descriptions);
+    }
+
+    #[test]
+    fn bucket_reset_only_when_empty() {
+        let headers = |remaining: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-ratelimit-remaining", HeaderValue::from_static(remaining));
+            headers.insert("x-ratelimit-reset-after", HeaderValue::from_static("1.5"));
+            headers
+        };
+
+        assert!(bucket_reset(&headers("4")).is_none());
+        let reset = bucket_reset(&headers("0")).unwrap();
+        assert!(reset > Instant::now() + Duration::from_secs(1));
+        assert!(bucket_reset(&HeaderMap::new()).is_none());
+    }
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{error_for_status, Notification, Notifier};
+
+// POSTs the notification as is, for anything able to receive a JSON webhook.
+pub struct JsonNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl JsonNotifier {
+    pub fn new(client: reqwest::Client, url: &str) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for JsonNotifier {
+    async fn send(&self, notification: &Notification) -> Result<Option<String>> {
+        let resp = self
+            .client
+            .post(&self.url)
+            .json(notification)
+            .send()
+            .await?;
+        error_for_status(resp).await?;
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::notify::{test_notification, DeliveryError};
+
+    #[tokio::test]
+    async fn posts_the_notification() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let message = JsonNotifier::new(reqwest::Client::new(), &format!("{}/hook", server.uri()))
+            .send(&test_notification())
+            .await
+            .unwrap();
+
+        assert!(message.is_none());
+        let requests = server.received_requests().await.unwrap();
+        let payload: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(
+            payload,
+            json!({
+                "title": "Server issues",
+                "description": "Servers are down <EU>",
+                "level": "error",
+                "color": null,
+                "fields": [
+                    { "name": "Status", "value": { "text": "Ongoing" }, "inline": true },
+                    { "name": "Since", "value": { "time": "2024-01-01T10:00:00Z" }, "inline": true },
+                ],
+                "url": "https://status.escapefromtarkov.com",
+                "thumbnail": "https://example.com/logo.png",
+                "event": null,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_the_status_of_a_failure() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(503).insert_header("retry-after", "5"))
+            .mount(&server)
+            .await;
+
+        let e = JsonNotifier::new(reqwest::Client::new(), &format!("{}/hook", server.uri()))
+            .send(&test_notification())
+            .await
+            .unwrap_err();
+
+        let e = e.downcast_ref::<DeliveryError>().unwrap();
+        assert_eq!(e.status, reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(e.retry_after, Some(std::time::Duration::from_secs(5)));
+    }
+}
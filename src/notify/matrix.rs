@@ -0,0 +1,284 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{header::AUTHORIZATION, Url};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{error_for_status, escape_html, Notification, Notifier};
+
+#[derive(Deserialize)]
+struct SentEvent {
+    event_id: String,
+}
+
+// Posts `m.room.message` events through the client-server API, updates are sent as `m.replace` edits.
+pub struct MatrixNotifier {
+    client: reqwest::Client,
+    homeserver: Url,
+    access_token: String,
+    room_id: String,
+    // Makes transaction ids unique within the process, the timestamp keeps them unique across restarts.
+    txn_counter: AtomicU64,
+    // Content of the last event that failed to send and its transaction id, reused when the delivery queue
+    // retries it: a request that did reach the homeserver is then deduplicated instead of posted twice.
+    unsent: Mutex<Option<(Value, String)>>,
+}
+
+impl MatrixNotifier {
+    pub fn new(
+        client: reqwest::Client,
+        homeserver: &str,
+        access_token: &str,
+        room_id: &str,
+    ) -> Result<Self> {
+        let homeserver = Url::parse(homeserver)
+            .with_context(|| format!("Invalid homeserver URL {homeserver}"))?;
+
+        Ok(Self {
+            client,
+            homeserver,
+            access_token: access_token.to_string(),
+            room_id: room_id.to_string(),
+            txn_counter: AtomicU64::new(0),
+            unsent: Mutex::new(None),
+        })
+    }
+
+    async fn send_event(&self, content: Value) -> Result<String> {
+        let txn_id = self.txn_id(&content);
+        let result = self.put_event(&content, &txn_id).await;
+        *self.unsent.lock().unwrap() = result.is_err().then(|| (content, txn_id));
+
+        result
+    }
+
+    fn txn_id(&self, content: &Value) -> String {
+        if let Some((unsent, txn_id)) = &*self.unsent.lock().unwrap() {
+            if unsent == content {
+                return txn_id.clone();
+            }
+        }
+
+        format!(
+            "{}-{}",
+            Utc::now().timestamp_millis(),
+            self.txn_counter.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+
+    async fn put_event(&self, content: &Value, txn_id: &str) -> Result<String> {
+        let mut url = self.homeserver.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid homeserver URL {}", self.homeserver))?
+            .pop_if_empty()
+            .extend(["_matrix", "client", "v3", "rooms", self.room_id.as_str()])
+            .extend(["send", "m.room.message", txn_id]);
+
+        let resp = self
+            .client
+            .put(url)
+            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
+            .json(content)
+            .send()
+            .await?;
+        let sent: SentEvent = error_for_status(resp).await?.json().await?;
+
+        Ok(sent.event_id)
+    }
+}
+
+#[async_trait]
+impl Notifier for MatrixNotifier {
+    async fn send(&self, notification: &Notification) -> Result<Option<String>> {
+        let event_id = self.send_event(message_content(notification)).await?;
+
+        Ok(Some(event_id))
+    }
+
+    async fn update(&self, message_id: &str, notification: &Notification) -> Result<bool> {
+        let new_content = message_content(notification);
+
+        // Clients without edit support display the fallback body, prefixed by convention with "* "
+        let content = json!({
+            "msgtype": "m.text",
+            "body": format!("* {}", new_content["body"].as_str().unwrap_or_default()),
+            "m.new_content": new_content,
+            "m.relates_to": {
+                "rel_type": "m.replace",
+                "event_id": message_id,
+            },
+        });
+        self.send_event(content).await?;
+
+        Ok(true)
+    }
+}
+
+fn message_content(notification: &Notification) -> Value {
+    let mut body = format!("{}\n\n{}", notification.title, notification.description);
+    let mut html = format!(
+        "<h4>{}</h4><p>{}</p>",
+        escape_html(&notification.title),
+        escape_html(&notification.description).replace('\n', "<br>")
+    );
+
+    if !notification.fields.is_empty() {
+        body.push('\n');
+        html.push_str("<ul>");
+    }
+    for field in notification.fields.iter() {
+        let value = field.value.to_text();
+        body.push_str(&format!("\n{}: {value}", field.name));
+        html.push_str(&format!(
+            "<li><strong>{}</strong>: {}</li>",
+            escape_html(&field.name),
+            escape_html(&value)
+        ));
+    }
+    if !notification.fields.is_empty() {
+        html.push_str("</ul>");
+    }
+
+    if let Some(url) = &notification.url {
+        body.push_str(&format!("\n\n{url}"));
+        html.push_str(&format!(
+            "<p><a href=\"{}\">{}</a></p>",
+            escape_html(url),
+            escape_html(url)
+        ));
+    }
+
+    json!({
+        "msgtype": "m.text",
+        "body": body,
+        "format": "org.matrix.custom.html",
+        "formatted_body": html,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{header, method, path_regex},
+        Mock, MockServer, Request, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::notify::test_notification;
+
+    const SEND_PATH: &str =
+        "^/_matrix/client/v3/rooms/!room:example.org/send/m.room.message/[^/]+$";
+
+    async fn homeserver() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path_regex(SEND_PATH))
+            .and(header("authorization", "Bearer secret"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "event_id": "$event1" })),
+            )
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn notifier(server: &MockServer) -> MatrixNotifier {
+        MatrixNotifier::new(
+            reqwest::Client::new(),
+            &server.uri(),
+            "secret",
+            "!room:example.org",
+        )
+        .unwrap()
+    }
+
+    async fn sent(server: &MockServer) -> Vec<Request> {
+        server.received_requests().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn sends_room_messages() {
+        let server = homeserver().await;
+
+        let message = notifier(&server).send(&test_notification()).await.unwrap();
+
+        assert_eq!(message.as_deref(), Some("$event1"));
+        let content: Value = serde_json::from_slice(&sent(&server).await[0].body).unwrap();
+        assert_eq!(content["msgtype"], "m.text");
+        assert_eq!(
+            content["body"],
+            "Server issues\n\nServers are down <EU>\n\nStatus: Ongoing\nSince: 2024-01-01 10:00 UTC\n\n\
+            https://status.escapefromtarkov.com"
+        );
+        assert_eq!(content["format"], "org.matrix.custom.html");
+        assert_eq!(
+            content["formatted_body"],
+            "<h4>Server issues</h4><p>Servers are down &lt;EU&gt;</p>\
+            <ul><li><strong>Status</strong>: Ongoing</li><li><strong>Since</strong>: 2024-01-01 10:00 UTC</li></ul>\
+            <p><a href=\"https://status.escapefromtarkov.com\">https://status.escapefromtarkov.com</a></p>"
+        );
+    }
+
+    #[tokio::test]
+    async fn updates_are_replacements() {
+        let server = homeserver().await;
+        let notifier = notifier(&server);
+
+        notifier.send(&test_notification()).await.unwrap();
+        let updated = notifier
+            .update("$event1", &test_notification())
+            .await
+            .unwrap();
+
+        assert!(updated);
+        let requests = sent(&server).await;
+        let original: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        let edit: Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(
+            edit["m.relates_to"],
+            json!({ "rel_type": "m.replace", "event_id": "$event1" })
+        );
+        assert_eq!(edit["m.new_content"], original);
+        assert_eq!(
+            edit["body"].as_str().unwrap(),
+            format!("* {}", original["body"].as_str().unwrap())
+        );
+        // A reused transaction id would make the homeserver drop the edit as a duplicate
+        assert_ne!(requests[0].url.path(), requests[1].url.path());
+    }
+
+    #[tokio::test]
+    async fn retries_reuse_the_transaction_id() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path_regex(SEND_PATH))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path_regex(SEND_PATH))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "event_id": "$event1" })),
+            )
+            .mount(&server)
+            .await;
+        let notifier = notifier(&server);
+
+        assert!(notifier.send(&test_notification()).await.is_err());
+        notifier.send(&test_notification()).await.unwrap();
+        // Same content, but a new message since the previous one got through
+        notifier.send(&test_notification()).await.unwrap();
+
+        let requests = sent(&server).await;
+        assert_eq!(requests[0].url.path(), requests[1].url.path());
+        assert_ne!(requests[1].url.path(), requests[2].url.path());
+    }
+}
@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    config::{DestinationConfig, NotifierKind},
+    http::{self, body_snippet},
+    models::Event,
+};
+
+pub mod discord;
+pub mod json;
+pub mod matrix;
+pub mod queue;
+pub mod slack;
+pub mod stdout;
+pub mod telegram;
+
+// Unsuccessful answer from a chat service, kept typed so the delivery queue can tell what is worth retrying.
+#[derive(Error, Debug)]
+#[error("{status}: {snippet}")]
+pub struct DeliveryError {
+    pub status: StatusCode,
+    pub snippet: String,
+    pub retry_after: Option<Duration>,
+}
+
+impl DeliveryError {
+    pub async fn from_response(resp: reqwest::Response) -> Self {
+        let status = resp.status();
+        let header_retry_after = http::retry_after(resp.headers());
+        let body = resp.text().await.unwrap_or_default();
+
+        Self {
+            status,
+            snippet: body_snippet(&body),
+            retry_after: header_retry_after.or_else(|| body_retry_after(&body)),
+        }
+    }
+}
+
+// Like `reqwest::Response::error_for_status`, keeping what the delivery queue needs to retry.
+pub async fn error_for_status(resp: reqwest::Response) -> Result<reqwest::Response, DeliveryError> {
+    if resp.status().is_success() {
+        Ok(resp)
+    } else {
+        Err(DeliveryError::from_response(resp).await)
+    }
+}
+
+// Discord puts a number of seconds in `retry_after`, Telegram in `parameters.retry_after`.
+fn body_retry_after(body: &str) -> Option<Duration> {
+    let body: Value = serde_json::from_str(body).ok()?;
+    let seconds = body["retry_after"]
+        .as_f64()
+        .or_else(|| body["parameters"]["retry_after"].as_f64())?;
+
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+// Backend agnostic description of a message, each notifier renders it in its own format.
+#[derive(Serialize, Debug, Clone)]
+pub struct Notification {
+    pub title: String,
+    pub description: String,
+    pub level: Level,
+    // Replaces the color of the level.
+    pub color: Option<u32>,
+    pub fields: Vec<Field>,
+    pub url: Option<String>,
+    pub thumbnail: Option<String>,
+    // The event this notification is about, if any.
+    pub event: Option<Event>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Success,
+    Warning,
+    Error,
+}
+
+impl Notification {
+    pub fn color(&self) -> u32 {
+        self.color.unwrap_or_else(|| self.level.color())
+    }
+}
+
+impl Level {
+    pub fn color(&self) -> u32 {
+        match self {
+            Level::Success => 65280,
+            Level::Warning => 16753920,
+            Level::Error => 16711680,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub value: FieldValue,
+    pub inline: bool,
+}
+
+impl Field {
+    pub fn text(name: &str, value: &str, inline: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            value: FieldValue::Text(value.to_string()),
+            inline,
+        }
+    }
+
+    pub fn time(name: &str, value: DateTime<Utc>, inline: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            value: FieldValue::Time(value),
+            inline,
+        }
+    }
+}
+
+// Times are kept apart so backends able to display them relatively (e.g. Discord's `<t:..:R>`) can do so.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldValue {
+    Text(String),
+    Time(DateTime<Utc>),
+}
+
+impl FieldValue {
+    // Plain text rendering, for backends without any date formatting of their own.
+    pub fn to_text(&self) -> String {
+        match self {
+            FieldValue::Text(text) => text.clone(),
+            FieldValue::Time(time) => time.format("%Y-%m-%d %H:%M UTC").to_string(),
+        }
+    }
+}
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    // Posts a new message. Returns its id when the backend is able to edit it later on.
+    async fn send(&self, notification: &Notification) -> Result<Option<String>>;
+
+    // Replaces a message previously posted by `send`. Returns false when that message doesn't exist anymore.
+    async fn update(&self, _message_id: &str, _notification: &Notification) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+pub fn build(config: &DestinationConfig, client: reqwest::Client) -> Result<Box<dyn Notifier>> {
+    // required fields were checked when loading the config
+    let url = config.url.as_deref().unwrap_or_default();
+    let required = |value: &Option<String>, field: &str| {
+        value
+            .clone()
+            .with_context(|| format!("Destination \"{}\": missing {field}", config.name))
+    };
+
+    let notifier: Box<dyn Notifier> = match config.kind {
+        NotifierKind::Discord => Box::new(discord::DiscordNotifier::new(
+            client,
+            url,
+            config.username.clone(),
+            config.avatar_url.clone(),
+        )?),
+        NotifierKind::Slack => Box::new(slack::SlackNotifier::new(
+            client,
+            url,
+            config.username.clone(),
+            config.avatar_url.clone(),
+        )),
+        NotifierKind::Telegram => Box::new(telegram::TelegramNotifier::new(
+            client,
+            config.url.as_deref(),
+            &required(&config.bot_token, "bot_token")?,
+            &required(&config.chat_id, "chat_id")?,
+        )),
+        NotifierKind::Matrix => Box::new(matrix::MatrixNotifier::new(
+            client,
+            url,
+            &required(&config.access_token, "access_token")?,
+            &required(&config.room_id, "room_id")?,
+        )?),
+        NotifierKind::Json => Box::new(json::JsonNotifier::new(client, url)),
+    };
+
+    Ok(notifier)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Has a bit of everything the notifiers render: markup to escape, a time field, a link and a thumbnail.
+#[cfg(test)]
+fn test_notification() -> Notification {
+    use chrono::TimeZone;
+
+    Notification {
+        title: "Server issues".to_string(),
+        description: "Servers are down <EU>".to_string(),
+        level: Level::Error,
+        color: None,
+        fields: vec![
+            Field::text("Status", "Ongoing", true),
+            Field::time(
+                "Since",
+                Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap(),
+                true,
+            ),
+        ],
+        url: Some("https://status.escapefromtarkov.com".to_string()),
+        thumbnail: Some("https://example.com/logo.png".to_string()),
+        event: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_from_the_body() {
+        assert_eq!(
+            body_retry_after(r#"{"message": "You are being rate limited.", "retry_after": 1.5}"#),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            body_retry_after(r#"{"ok": false, "parameters": {"retry_after": 3}}"#),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(body_retry_after("<html>Bad gateway</html>"), None);
+        assert_eq!(body_retry_after(r#"{"retry_after": -1}"#), None);
+    }
+}
@@ -0,0 +1,186 @@
+use tokio::sync::{mpsc, oneshot};
+
+use std::{fmt, future::Future, sync::Arc, time::Duration};
+
+use reqwest::StatusCode;
+
+use super::{DeliveryError, Notification, Notifier};
+use crate::{
+    metrics,
+    retry::{RetryPolicy, Retryable},
+};
+
+struct Job {
+    notification: Notification,
+    message: Option<String>,
+    reply: oneshot::Sender<Delivery>,
+}
+
+// Outcome of a notification, once the retries are over.
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    // Id of the message now tracking the event, if any.
+    pub message: Option<String>,
+    // False when the notification didn't get through.
+    pub ok: bool,
+}
+
+impl Delivery {
+    fn sent(message: Option<String>) -> Self {
+        Self { message, ok: true }
+    }
+
+    fn failed(message: Option<String>) -> Self {
+        Self { message, ok: false }
+    }
+}
+
+// Delivers the notifications of one destination one at a time, in the order they were queued, so a burst
+// of events (e.g. after a restart) goes out in order at the pace the service allows.
+pub struct DeliveryQueue {
+    jobs: mpsc::UnboundedSender<Job>,
+}
+
+impl DeliveryQueue {
+    pub fn spawn(name: String, notifier: Arc<dyn Notifier>, policy: RetryPolicy) -> Self {
+        let (jobs, mut receiver) = mpsc::unbounded_channel::<Job>();
+        let worker = Worker {
+            name,
+            notifier,
+            policy,
+        };
+
+        tokio::spawn(async move {
+            while let Some(job) = receiver.recv().await {
+                let delivery = worker.deliver(&job.notification, job.message).await;
+                // Nobody to tell if the caller stopped waiting
+                let _ = job.reply.send(delivery);
+            }
+        });
+
+        Self { jobs }
+    }
+
+    // Waits for the notification to be delivered, see `Worker::deliver`.
+    pub async fn deliver(&self, notification: Notification, message: Option<String>) -> Delivery {
+        let (reply, response) = oneshot::channel();
+        let job = Job {
+            notification,
+            message: message.clone(),
+            reply,
+        };
+
+        // Only fails if the worker panicked, keep the message so it can still be edited later on
+        if self.jobs.send(job).is_err() {
+            return Delivery::failed(message);
+        }
+
+        response.await.unwrap_or_else(|_| Delivery::failed(message))
+    }
+}
+
+struct Worker {
+    name: String,
+    notifier: Arc<dyn Notifier>,
+    policy: RetryPolicy,
+}
+
+impl Worker {
+    // Updates the previous message if there is one, otherwise posts a new one.
+    // Errors are only logged so one failing destination doesn't prevent delivery to the others,
+    // the caller finds out through `Delivery::ok`.
+    async fn deliver(&self, notification: &Notification, message: Option<String>) -> Delivery {
+        if let Some(message) = message {
+            let id = &message;
+            let what = format!("[{}] Update of message {id}", self.name);
+            match self
+                .policy
+                .run(&what, || {
+                    self.measure(self.notifier.update(id, notification))
+                })
+                .await
+            {
+                Ok(true) => return Delivery::sent(Some(message)),
+                Ok(false) => warn!(
+                    "[{}] Message {id} no longer exists, posting a new one",
+                    self.name
+                ),
+                Err(e) => {
+                    error!("[{}] Failed to update message {id}: {e}", self.name);
+                    return Delivery::failed(Some(message));
+                }
+            }
+        }
+
+        let what = format!("[{}] Message delivery", self.name);
+        match self
+            .policy
+            .run(&what, || self.measure(self.notifier.send(notification)))
+            .await
+        {
+            Ok(message) => Delivery::sent(message),
+            Err(e) => {
+                error!("[{}] Failed to send message: {e}", self.name);
+                Delivery::failed(None)
+            }
+        }
+    }
+
+    // Records the outcome and latency of a single attempt.
+    async fn measure<T>(
+        &self,
+        attempt: impl Future<Output = anyhow::Result<T>>,
+    ) -> Result<T, AttemptError> {
+        let metrics = metrics::get();
+        let timer = metrics
+            .delivery_duration
+            .with_label_values(&[self.name.as_str()])
+            .start_timer();
+        let result = attempt.await;
+        timer.observe_duration();
+
+        let status = match &result {
+            Ok(_) => "ok".to_string(),
+            Err(e) => match e.downcast_ref::<DeliveryError>() {
+                Some(e) => e.status.as_u16().to_string(),
+                None => "error".to_string(),
+            },
+        };
+        metrics
+            .deliveries
+            .with_label_values(&[self.name.as_str(), status.as_str()])
+            .inc();
+
+        result.map_err(AttemptError)
+    }
+}
+
+// Error of a delivery attempt, as notifiers return it.
+#[derive(Debug)]
+struct AttemptError(anyhow::Error);
+
+impl fmt::Display for AttemptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// Only rate limits, server errors and failed connections are retried.
+// Other request errors may have reached the server, retrying them could post the message twice.
+impl Retryable for AttemptError {
+    fn is_retryable(&self) -> bool {
+        if let Some(e) = self.0.downcast_ref::<DeliveryError>() {
+            return e.status == StatusCode::TOO_MANY_REQUESTS || e.status.is_server_error();
+        }
+
+        self.0
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect())
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        self.0
+            .downcast_ref::<DeliveryError>()
+            .and_then(|e| e.retry_after)
+    }
+}
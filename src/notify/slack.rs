@@ -0,0 +1,223 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{error_for_status, FieldValue, Notification, Notifier};
+
+// Slack refuses sections holding more fields than this.
+const MAX_SECTION_FIELDS: usize = 10;
+
+// Posts Block Kit messages to a Slack incoming webhook. Those can't be edited, so every update is a new message.
+pub struct SlackNotifier {
+    client: reqwest::Client,
+    url: String,
+    username: Option<String>,
+    icon_url: Option<String>,
+}
+
+impl SlackNotifier {
+    pub fn new(
+        client: reqwest::Client,
+        url: &str,
+        username: Option<String>,
+        icon_url: Option<String>,
+    ) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            username,
+            icon_url,
+        }
+    }
+
+    fn build_payload(&self, notification: &Notification) -> Value {
+        let mut blocks = vec![json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": notification.title },
+        })];
+
+        let mut section = json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": escape_mrkdwn(&notification.description) },
+        });
+        if let Some(thumbnail) = &notification.thumbnail {
+            section["accessory"] = json!({
+                "type": "image",
+                "image_url": thumbnail,
+                "alt_text": notification.title,
+            });
+        }
+        blocks.push(section);
+
+        for fields in notification.fields.chunks(MAX_SECTION_FIELDS) {
+            let fields: Vec<Value> = fields
+                .iter()
+                .map(|field| {
+                    let value = match &field.value {
+                        FieldValue::Time(time) => format!(
+                            "<!date^{}^{{date_short_pretty}} {{time}}|{}>",
+                            time.timestamp(),
+                            field.value.to_text()
+                        ),
+                        FieldValue::Text(text) => escape_mrkdwn(text),
+                    };
+                    json!({ "type": "mrkdwn", "text": format!("*{}*\n{value}", escape_mrkdwn(&field.name)) })
+                })
+                .collect();
+            blocks.push(json!({ "type": "section", "fields": fields }));
+        }
+
+        if let Some(url) = &notification.url {
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": format!("<{url}>") }],
+            }));
+        }
+
+        let mut payload = json!({
+            // shown in notifications, where blocks aren't rendered
+            "text": notification.title,
+            "blocks": blocks,
+        });
+        if let Some(username) = &self.username {
+            payload["username"] = json!(username);
+        }
+        if let Some(icon_url) = &self.icon_url {
+            payload["icon_url"] = json!(icon_url);
+        }
+
+        payload
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    async fn send(&self, notification: &Notification) -> Result<Option<String>> {
+        let resp = self
+            .client
+            .post(&self.url)
+            .json(&self.build_payload(notification))
+            .send()
+            .await?;
+        error_for_status(resp).await?;
+
+        Ok(None)
+    }
+}
+
+// Slack only requires these three characters to be escaped in mrkdwn text.
+fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::notify::{test_notification, DeliveryError, Field};
+
+    const WEBHOOK_PATH: &str = "/services/T000/B000/XXXX";
+
+    fn notifier(server: &MockServer) -> SlackNotifier {
+        SlackNotifier::new(
+            reqwest::Client::new(),
+            &format!("{}{WEBHOOK_PATH}", server.uri()),
+            Some("Tarkov".to_string()),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn posts_block_kit() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(WEBHOOK_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let message = notifier(&server).send(&test_notification()).await.unwrap();
+
+        // Incoming webhooks can't be edited
+        assert!(message.is_none());
+        let requests = server.received_requests().await.unwrap();
+        let payload: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(
+            payload,
+            json!({
+                "text": "Server issues",
+                "username": "Tarkov",
+                "blocks": [
+                    {
+                        "type": "header",
+                        "text": { "type": "plain_text", "text": "Server issues" },
+                    },
+                    {
+                        "type": "section",
+                        "text": { "type": "mrkdwn", "text": "Servers are down &lt;EU&gt;" },
+                        "accessory": {
+                            "type": "image",
+                            "image_url": "https://example.com/logo.png",
+                            "alt_text": "Server issues",
+                        },
+                    },
+                    {
+                        "type": "section",
+                        "fields": [
+                            { "type": "mrkdwn", "text": "*Status*\nOngoing" },
+                            {
+                                "type": "mrkdwn",
+                                "text": "*Since*\n<!date^1704103200^{date_short_pretty} {time}|2024-01-01 10:00 UTC>",
+                            },
+                        ],
+                    },
+                    {
+                        "type": "context",
+                        "elements": [{ "type": "mrkdwn", "text": "<https://status.escapefromtarkov.com>" }],
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn splits_fields_into_sections_of_ten() {
+        let mut notification = test_notification();
+        notification.fields = (0..12)
+            .map(|i| Field::text(&i.to_string(), "value", true))
+            .collect();
+
+        let notifier = SlackNotifier::new(reqwest::Client::new(), "http://localhost", None, None);
+        let payload = notifier.build_payload(&notification);
+
+        let blocks = payload["blocks"].as_array().unwrap();
+        assert_eq!(blocks[2]["fields"].as_array().unwrap().len(), 10);
+        assert_eq!(blocks[3]["fields"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_status_of_a_rejected_message() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(WEBHOOK_PATH))
+            .respond_with(ResponseTemplate::new(404).set_body_string("no_service"))
+            .mount(&server)
+            .await;
+
+        let e = notifier(&server)
+            .send(&test_notification())
+            .await
+            .unwrap_err();
+
+        let e = e.downcast_ref::<DeliveryError>().unwrap();
+        assert_eq!(e.status, reqwest::StatusCode::NOT_FOUND);
+        assert_eq!(e.snippet, "no_service");
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{Notification, Notifier};
+
+// Prints notifications instead of sending them, for dry runs.
+pub struct StdoutNotifier {
+    name: String,
+}
+
+impl StdoutNotifier {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+        }
+    }
+
+    fn print(&self, header: &str, notification: &Notification) -> Result<()> {
+        println!("{header}\n{}", serde_json::to_string_pretty(notification)?);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for StdoutNotifier {
+    async fn send(&self, notification: &Notification) -> Result<Option<String>> {
+        self.print(&format!("[{}]", self.name), notification)?;
+        Ok(None)
+    }
+
+    // Dry runs load the saved state, so the events already posted are edited rather than posted again.
+    async fn update(&self, message_id: &str, notification: &Notification) -> Result<bool> {
+        self.print(
+            &format!("[{}] edit of message {message_id}", self.name),
+            notification,
+        )?;
+        Ok(true)
+    }
+}
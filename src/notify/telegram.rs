@@ -0,0 +1,316 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{escape_html, DeliveryError, Notification, Notifier};
+
+const DEFAULT_API_URL: &str = "https://api.telegram.org";
+
+#[derive(Deserialize)]
+struct ApiResponse {
+    ok: bool,
+    description: Option<String>,
+    result: Option<Value>,
+}
+
+// Sends messages through the Bot API `sendMessage` method, updates go through `editMessageText`.
+pub struct TelegramNotifier {
+    client: reqwest::Client,
+    // https://api.telegram.org/bot<token>
+    bot_url: String,
+    chat_id: String,
+}
+
+impl TelegramNotifier {
+    pub fn new(
+        client: reqwest::Client,
+        api_url: Option<&str>,
+        bot_token: &str,
+        chat_id: &str,
+    ) -> Self {
+        let api_url = api_url.unwrap_or(DEFAULT_API_URL).trim_end_matches('/');
+
+        Self {
+            client,
+            bot_url: format!("{api_url}/bot{bot_token}"),
+            chat_id: chat_id.to_string(),
+        }
+    }
+
+    // The Bot API answers with `ok: false` and a description instead of relying on the HTTP status alone.
+    async fn call(&self, method: &str, body: Value) -> Result<ApiResponse> {
+        let resp = self
+            .client
+            .post(format!("{}/{method}", self.bot_url))
+            .json(&body)
+            .send()
+            .await?;
+
+        // Left to the delivery queue, which retries them
+        let status = resp.status();
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(DeliveryError::from_response(resp).await.into());
+        }
+
+        Ok(resp.json().await?)
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    async fn send(&self, notification: &Notification) -> Result<Option<String>> {
+        let resp = self
+            .call(
+                "sendMessage",
+                json!({
+                    "chat_id": self.chat_id,
+                    "text": format_html(notification),
+                    "parse_mode": "HTML",
+                    "disable_web_page_preview": true,
+                }),
+            )
+            .await?;
+
+        if !resp.ok {
+            bail!(
+                "Telegram sendMessage failed: {}",
+                resp.description.unwrap_or_default()
+            );
+        }
+
+        Ok(resp
+            .result
+            .and_then(|message| message["message_id"].as_i64())
+            .map(|id| id.to_string()))
+    }
+
+    async fn update(&self, message_id: &str, notification: &Notification) -> Result<bool> {
+        let resp = self
+            .call(
+                "editMessageText",
+                json!({
+                    "chat_id": self.chat_id,
+                    "message_id": message_id.parse::<i64>()?,
+                    "text": format_html(notification),
+                    "parse_mode": "HTML",
+                    "disable_web_page_preview": true,
+                }),
+            )
+            .await?;
+
+        if resp.ok {
+            return Ok(true);
+        }
+
+        let description = resp.description.unwrap_or_default();
+        if description.contains("message is not modified") {
+            return Ok(true);
+        }
+        if description.contains("message to edit not found") {
+            return Ok(false);
+        }
+
+        bail!("Telegram editMessageText failed: {description}")
+    }
+}
+
+// Telegram's HTML parse mode has no line break tag, plain new lines are kept as is.
+fn format_html(notification: &Notification) -> String {
+    let mut text = format!(
+        "<b>{}</b>\n\n{}",
+        escape_html(&notification.title),
+        escape_html(&notification.description)
+    );
+
+    if !notification.fields.is_empty() {
+        text.push('\n');
+    }
+    for field in notification.fields.iter() {
+        text.push_str(&format!(
+            "\n<b>{}</b>: {}",
+            escape_html(&field.name),
+            escape_html(&field.value.to_text())
+        ));
+    }
+
+    if let Some(url) = &notification.url {
+        text.push_str(&format!(
+            "\n\n<a href=\"{}\">{}</a>",
+            escape_html(url),
+            escape_html(url)
+        ));
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use wiremock::{
+        matchers::{body_partial_json, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::notify::test_notification;
+
+    const TEXT: &str =
+        "<b>Server issues</b>\n\nServers are down &lt;EU&gt;\n\n<b>Status</b>: Ongoing\n\
+        <b>Since</b>: 2024-01-01 10:00 UTC\n\n\
+        <a href=\"https://status.escapefromtarkov.com\">https://status.escapefromtarkov.com</a>";
+
+    fn notifier(server: &MockServer) -> TelegramNotifier {
+        TelegramNotifier::new(
+            reqwest::Client::new(),
+            Some(&server.uri()),
+            "123:token",
+            "-1001234",
+        )
+    }
+
+    async fn answer(server: &MockServer, api_method: &str, status: u16, body: Value) {
+        Mock::given(method("POST"))
+            .and(path(format!("/bot123:token/{api_method}")))
+            .respond_with(ResponseTemplate::new(status).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn sends_html_messages() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:token/sendMessage"))
+            .and(body_partial_json(json!({
+                "chat_id": "-1001234",
+                "text": TEXT,
+                "parse_mode": "HTML",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "ok": true,
+                "result": { "message_id": 42 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let message = notifier(&server).send(&test_notification()).await.unwrap();
+
+        assert_eq!(message.as_deref(), Some("42"));
+    }
+
+    #[tokio::test]
+    async fn send_fails_when_not_ok() {
+        let server = MockServer::start().await;
+        answer(
+            &server,
+            "sendMessage",
+            400,
+            json!({ "ok": false, "description": "Bad Request: chat not found" }),
+        )
+        .await;
+
+        let e = notifier(&server)
+            .send(&test_notification())
+            .await
+            .unwrap_err();
+
+        assert!(e.to_string().contains("chat not found"));
+    }
+
+    #[tokio::test]
+    async fn edits_the_message() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:token/editMessageText"))
+            .and(body_partial_json(json!({
+                "chat_id": "-1001234",
+                "message_id": 42,
+                "text": TEXT,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "ok": true,
+                "result": { "message_id": 42 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let updated = notifier(&server)
+            .update("42", &test_notification())
+            .await
+            .unwrap();
+
+        assert!(updated);
+    }
+
+    #[tokio::test]
+    async fn unchanged_message_counts_as_edited() {
+        let server = MockServer::start().await;
+        answer(
+            &server,
+            "editMessageText",
+            400,
+            json!({
+                "ok": false,
+                "description": "Bad Request: message is not modified: specified new message content and reply markup are exactly the same as a current content and reply markup of the message"
+            }),
+        )
+        .await;
+
+        let updated = notifier(&server)
+            .update("42", &test_notification())
+            .await
+            .unwrap();
+
+        assert!(updated);
+    }
+
+    #[tokio::test]
+    async fn deleted_message_is_not_edited() {
+        let server = MockServer::start().await;
+        answer(
+            &server,
+            "editMessageText",
+            400,
+            json!({ "ok": false, "description": "Bad Request: message to edit not found" }),
+        )
+        .await;
+
+        let updated = notifier(&server)
+            .update("42", &test_notification())
+            .await
+            .unwrap();
+
+        assert!(!updated);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_is_left_to_the_queue() {
+        let server = MockServer::start().await;
+        answer(
+            &server,
+            "sendMessage",
+            429,
+            json!({
+                "ok": false,
+                "description": "Too Many Requests: retry after 3",
+                "parameters": { "retry_after": 3 }
+            }),
+        )
+        .await;
+
+        let e = notifier(&server)
+            .send(&test_notification())
+            .await
+            .unwrap_err();
+
+        let e = e.downcast_ref::<DeliveryError>().unwrap();
+        assert_eq!(e.status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(e.retry_after, Some(Duration::from_secs(3)));
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{Duration, Utc};
+
+use crate::{
+    config::EventStyle,
+    locale::Locale,
+    maintenance::{Reminder, Schedule},
+    models::{Event, EventType, ServiceState},
+    notify::{Field, Level, Notification},
+    services::ServiceChange,
+};
+
+const STATUS_PAGE_URL: &str = "https://status.escapefromtarkov.com";
+const LOGO_URL: &str = "https://www.escapefromtarkov.com/themes/eft/images/logo.png";
+
+/// What happened to an event before this notification, summarized once it's resolved.
+pub struct History {
+    /// Times the content of the event changed after it was first posted.
+    pub revisions: u32,
+}
+
+pub fn incident(
+    event: &Event,
+    translated_content: &str,
+    locale: &Locale,
+    style: Option<&EventStyle>,
+    history: &History,
+) -> Notification {
+    let mut fields = Vec::new();
+
+    // tweak some params if solved
+    let level = if let Some(solve_time) = event.solve_time {
+        fields.push(Field::time(locale.resolved_since, solve_time, true));
+        fields.push(Field::time(locale.started, event.time, true));
+        fields.push(Field::text(
+            locale.duration,
+            &format_duration(solve_time - event.time, locale),
+            true,
+        ));
+        fields.push(Field::text(
+            locale.updates,
+            &history.revisions.to_string(),
+            true,
+        ));
+        fields.push(Field::text(
+            locale.status,
+            &format!("{} ✅", locale.resolved),
+            false,
+        ));
+        Level::Success
+
+    // or not
+    } else {
+        fields.push(Field::time(locale.since, event.time, true));
+        fields.push(Field::text(
+            locale.status,
+            &format!("{} ❎", locale.offline),
+            false,
+        ));
+        Level::Error
+    };
+
+    let mut title = match (
+        style.and_then(|style| style.title.as_ref()),
+        event.event_type,
+    ) {
+        (Some(title), _) => title.clone(),
+        // keep the code around, it's the only clue about what the type means
+        (None, EventType::Other(code)) => format!("{} ({code})", locale.unknown),
+        (None, event_type) => locale.event_type(event_type).to_string(),
+    };
+    if let Some(emoji) = style.and_then(|style| style.emoji.as_ref()) {
+        title = format!("{emoji} {title}");
+    }
+
+    Notification {
+        title,
+        description: translated_content.to_string(),
+        level,
+        color: style
+            .and_then(|style| style.color())
+            .filter(|_| event.solve_time.is_none()),
+        fields,
+        url: Some(STATUS_PAGE_URL.to_string()),
+        thumbnail: Some(LOGO_URL.to_string()),
+        event: Some(event.clone()),
+    }
+}
+
+/// Made up event for `test-webhook`, laid out like a real one.
+pub fn sample(locale: &Locale) -> Notification {
+    let event = Event {
+        id: "test".to_string(),
+        content: "Test notification from tarkov-status, this destination is set up right."
+            .to_string(),
+        event_type: EventType::Announcement,
+        time: Utc::now(),
+        solve_time: None,
+        start_time: None,
+        end_time: None,
+    };
+    let history = History { revisions: 0 };
+
+    incident(&event, &event.content, locale, None, &history)
+}
+
+/// Sent to the `[shutdown] notify` destination when the bot stops.
+pub fn offline(locale: &Locale) -> Notification {
+    Notification {
+        title: locale.going_offline.to_string(),
+        description: locale.going_offline_description.to_string(),
+        level: Level::Warning,
+        color: None,
+        fields: Vec::new(),
+        url: None,
+        thumbnail: Some(LOGO_URL.to_string()),
+        event: None,
+    }
+}
+
+pub fn service(change: &ServiceChange, locale: &Locale) -> Notification {
+    let level = match change.to {
+        ServiceState::Operational => Level::Success,
+        ServiceState::Down => Level::Error,
+        _ => Level::Warning,
+    };
+
+    Notification {
+        title: format!("{}: {}", change.name, locale.service_state(change.to)),
+        description: change.message.clone().unwrap_or_default(),
+        level,
+        color: None,
+        fields: vec![Field::text(
+            locale.status,
+            &format!(
+                "{} → {}",
+                locale.service_state(change.from),
+                locale.service_state(change.to)
+            ),
+            false,
+        )],
+        url: Some(STATUS_PAGE_URL.to_string()),
+        thumbnail: Some(LOGO_URL.to_string()),
+        event: None,
+    }
+}
+
+pub fn maintenance(
+    reminder: Reminder,
+    schedule: &Schedule,
+    translated_content: &str,
+    locale: &Locale,
+    style: Option<&EventStyle>,
+) -> Notification {
+    let (title, level) = match reminder {
+        Reminder::Upcoming => (locale.maintenance_upcoming, Level::Warning),
+        Reminder::Started => (locale.maintenance_started, Level::Warning),
+        Reminder::Overrun => (locale.maintenance_overrun, Level::Error),
+    };
+
+    let mut fields = Vec::new();
+    if reminder == Reminder::Upcoming {
+        fields.push(Field::time(locale.starts, schedule.start, true));
+    }
+    if let Some(end) = schedule.end {
+        fields.push(Field::time(locale.expected_end, end, true));
+    }
+
+    Notification {
+        title: match style.and_then(|style| style.emoji.as_ref()) {
+            Some(emoji) => format!("{emoji} {title}"),
+            None => title.to_string(),
+        },
+        description: translated_content.to_string(),
+        level,
+        color: None,
+        fields,
+        url: Some(STATUS_PAGE_URL.to_string()),
+        thumbnail: Some(LOGO_URL.to_string()),
+        event: None,
+    }
+}
+
+/// "2 h 05 min", or only the minutes under an hour.
+pub fn format_duration(duration: Duration, locale: &Locale) -> String {
+    let minutes = duration.num_minutes().max(0);
+
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes} {}", locale.minutes),
+        (hours, minutes) => format!("{hours} {} {minutes:02} {}", locale.hours, locale.minutes),
+    }
+}
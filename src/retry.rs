@@ -0,0 +1,235 @@
+use std::{fmt::Display, future::Future, time::Duration};
+
+use rand::Rng;
+use tokio::time;
+
+use crate::config::RetryConfig;
+
+pub trait Retryable {
+    // Whether trying again later has a chance to succeed.
+    fn is_retryable(&self) -> bool;
+
+    // Delay requested by the server, e.g. through a Retry-After header.
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl From<&RetryConfig> for RetryPolicy {
+    fn from(config: &RetryConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts,
+            initial_delay: Duration::from_millis(config.initial_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms),
+        }
+    }
+}
+
+impl RetryPolicy {
+    // Exponential backoff capped to `max_delay`, randomized between half and all of it
+    // so several clients don't retry in lockstep.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self
+            .initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay);
+
+        let half = delay.as_millis() as u64 / 2;
+        Duration::from_millis(half + rand::thread_rng().gen_range(0..=half))
+    }
+
+    // Runs `op` until it succeeds, fails with an error that isn't worth retrying, or runs out of attempts.
+    pub async fn run<T, E, F, Fut>(&self, what: &str, mut op: F) -> Result<T, E>
+    where
+        E: Retryable + Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            let e = match op().await {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+
+            if attempt >= self.max_attempts || !e.is_retryable() {
+                return Err(e);
+            }
+
+            let delay = match e.retry_after() {
+                // Not worth holding on to, the next attempt will come soon enough
+                Some(delay) if delay > self.max_delay => return Err(e),
+                Some(delay) => delay,
+                None => self.backoff(attempt),
+            };
+
+            warn!(
+                "{what} failed (attempt {attempt}/{}), retrying in {delay:?}: {e}",
+                self.max_attempts
+            );
+            time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fmt, time::Instant};
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct TestError {
+        retryable: bool,
+        retry_after: Option<Duration>,
+    }
+
+    impl TestError {
+        fn transient() -> Self {
+            Self {
+                retryable: true,
+                retry_after: None,
+            }
+        }
+    }
+
+    impl fmt::Display for TestError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "test error")
+        }
+    }
+
+    impl Retryable for TestError {
+        fn is_retryable(&self) -> bool {
+            self.retryable
+        }
+
+        fn retry_after(&self) -> Option<Duration> {
+            self.retry_after
+        }
+    }
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(50),
+        }
+    }
+
+    // Fails with `error()` for the first `failures` calls, returns the number of calls made so far.
+    async fn run(
+        policy: &RetryPolicy,
+        failures: u32,
+        error: impl Fn() -> TestError,
+    ) -> (Result<u32, TestError>, u32) {
+        let mut calls = 0;
+        let result = policy
+            .run("test", || {
+                calls += 1;
+                let result = if calls <= failures {
+                    Err(error())
+                } else {
+                    Ok(calls)
+                };
+                async move { result }
+            })
+            .await;
+
+        (result, calls)
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let (result, calls) = run(&policy(5), 2, TestError::transient).await;
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_errors_not_worth_retrying() {
+        let (result, calls) = run(&policy(5), 5, || TestError {
+            retryable: false,
+            retry_after: None,
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn stops_after_max_attempts() {
+        let (result, calls) = run(&policy(3), 5, TestError::transient).await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn waits_as_long_as_the_server_asks() {
+        let start = Instant::now();
+        let (result, calls) = run(&policy(3), 1, || TestError {
+            retryable: true,
+            retry_after: Some(Duration::from_millis(30)),
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(calls, 2);
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn leaves_long_retry_after_to_the_next_poll() {
+        let start = Instant::now();
+        let (result, calls) = run(&policy(3), 5, || TestError {
+            retryable: true,
+            retry_after: Some(Duration::from_secs(3600)),
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max_delay_with_jitter() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+        };
+
+        for (attempt, expected) in [
+            (0, 100),
+            (1, 100),
+            (2, 200),
+            (3, 400),
+            (4, 800),
+            (5, 1000),
+            (100, 1000),
+        ] {
+            for _ in 0..50 {
+                let delay = policy.backoff(attempt).as_millis();
+                assert!(
+                    (expected / 2..=expected).contains(&delay),
+                    "attempt {attempt}: {delay}ms not within {}..={expected}ms",
+                    expected / 2
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde::Serialize;
+
+use crate::{metrics, state::SavedEvents};
+
+// What the main loop reports about itself, served by the health endpoints.
+#[derive(Serialize, Debug, Clone)]
+pub struct Health {
+    pub started_at: DateTime<Utc>,
+    // Start of the last poll, successful or not.
+    pub last_poll: Option<DateTime<Utc>>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub events: SavedEvents,
+}
+
+pub type SharedHealth = Arc<Mutex<Health>>;
+
+impl Health {
+    pub fn shared() -> SharedHealth {
+        Arc::new(Mutex::new(Self {
+            started_at: Utc::now(),
+            last_poll: None,
+            last_success: None,
+            last_error: None,
+            events: SavedEvents::new(),
+        }))
+    }
+
+    fn within(time: Option<DateTime<Utc>>, threshold: Duration) -> bool {
+        time.is_some_and(|time| (Utc::now() - time).to_std().unwrap_or_default() <= threshold)
+    }
+
+    // The loop keeps ticking, even if the status API doesn't answer.
+    fn is_alive(&self, threshold: Duration) -> bool {
+        Self::within(self.last_poll.or(Some(self.started_at)), threshold)
+    }
+
+    fn is_ready(&self, threshold: Duration) -> bool {
+        Self::within(self.last_success, threshold)
+    }
+}
+
+// Binds right away so a taken port fails the startup, then serves in the background.
+pub fn start(listen: SocketAddr, health: SharedHealth, threshold: Duration) -> Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let health = health.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let response = handle(&req, &health, threshold);
+                async move { Ok::<_, Infallible>(response) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&listen)
+        .with_context(|| format!("Failed to listen on {listen}"))?
+        .serve(make_service);
+    info!("Serving health endpoints on {listen}");
+
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            error!("Health server stopped: {e}");
+        }
+    });
+
+    Ok(())
+}
+
+fn handle(req: &Request<Body>, health: &Mutex<Health>, threshold: Duration) -> Response<Body> {
+    if req.method() != Method::GET {
+        return text(StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+    }
+
+    let health = health.lock().unwrap();
+    match req.uri().path() {
+        "/healthz" if health.is_alive(threshold) => text(StatusCode::OK, "ok"),
+        "/healthz" => text(StatusCode::SERVICE_UNAVAILABLE, "poll loop stalled"),
+        "/readyz" if health.is_ready(threshold) => text(StatusCode::OK, "ok"),
+        "/readyz" => text(StatusCode::SERVICE_UNAVAILABLE, "no recent successful poll"),
+        "/metrics" => Response::builder()
+            .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(Body::from(metrics::encode()))
+            .unwrap(),
+        "/state" => match serde_json::to_string_pretty(&*health) {
+            Ok(json) => Response::builder()
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(json))
+                .unwrap(),
+            Err(e) => text(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+        },
+        _ => text(StatusCode::NOT_FOUND, "not found"),
+    }
+}
+
+fn text(status: StatusCode, body: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
@@ -0,0 +1,389 @@
+use std::iter;
+
+use futures::future::join_all;
+
+use crate::{
+    destination::Destination,
+    models::{GlobalStatus, ServiceState, ServiceStatus},
+    render,
+    retry::RetryPolicy,
+    source::StatusSource,
+    state::{PendingChange, SavedService, ServiceStates},
+};
+
+// Name the global status is reported under.
+const GLOBAL_NAME: &str = "Escape from Tarkov";
+
+#[derive(Debug, Clone)]
+pub struct ServiceChange {
+    pub name: String,
+    pub from: ServiceState,
+    pub to: ServiceState,
+    // Only set for the global status.
+    pub message: Option<String>,
+}
+
+// Watches the per service and global health, which usually reflect outages before BSG posts a message about them.
+#[derive(Default)]
+pub struct ServiceMonitor {
+    states: ServiceStates,
+}
+
+impl ServiceMonitor {
+    // Picks up from the states saved by a previous run.
+    pub fn new(states: ServiceStates) -> Self {
+        Self { states }
+    }
+
+    pub fn states(&self) -> &ServiceStates {
+        &self.states
+    }
+
+    // Records the new states and returns the transitions. The first state seen for a service is only recorded,
+    // so a restart doesn't notify about everything that isn't operational.
+    pub fn update(
+        &mut self,
+        services: &[ServiceStatus],
+        global: &GlobalStatus,
+    ) -> Vec<ServiceChange> {
+        let mut changes = Vec::new();
+
+        let statuses = iter::once((GLOBAL_NAME, global.status, global.message.as_deref())).chain(
+            services
+                .iter()
+                .map(|service| (service.name.as_str(), service.status, None)),
+        );
+        for (name, state, message) in statuses {
+            // Nothing to say about a state we can't make sense of
+            if state == ServiceState::Unknown {
+                continue;
+            }
+
+            let previous = self.states.get(name).map(|saved| saved.state);
+            if previous == Some(state) {
+                continue;
+            }
+            // A transition still pending is superseded by this one, which goes to every destination
+            self.states.insert(
+                name.to_string(),
+                SavedService {
+                    state,
+                    pending: None,
+                },
+            );
+            if let Some(previous) = previous {
+                changes.push(ServiceChange {
+                    name: name.to_string(),
+                    from: previous,
+                    to: state,
+                    message: message.map(str::to_string),
+                });
+            }
+        }
+
+        changes
+    }
+
+    // Fetches the current states and notifies the destinations taking service changes of the transitions,
+    // then tries the transitions missed on previous polls again, only for the destinations that missed them.
+    // Returns whether anything to save changed, including services seen for the first time.
+    pub async fn poll(
+        &mut self,
+        source: &dyn StatusSource,
+        retry_policy: &RetryPolicy,
+        destinations: &[Destination],
+    ) -> bool {
+        let statuses = retry_policy
+            .run("Service status poll", || source.service_statuses())
+            .await;
+        let (services, global) = match statuses {
+            Ok(Some(statuses)) => statuses,
+            Ok(None) => return false,
+            Err(e) => {
+                warn!("Skipping the service status check: {e}");
+                return false;
+            }
+        };
+
+        let before = self.states.clone();
+
+        let mut notices: Vec<(ServiceChange, Vec<&Destination>)> = Vec::new();
+        for change in self.update(&services, &global) {
+            info!(
+                "Service {} went from {:?} to {:?}",
+                change.name, change.from, change.to
+            );
+            let targets = destinations
+                .iter()
+                .filter(|destination| destination.services)
+                .collect();
+            notices.push((change, targets));
+        }
+        for (name, saved) in self.states.iter() {
+            let Some(pending) = &saved.pending else {
+                continue;
+            };
+            info!(
+                "Retrying the change of service {name} for {}",
+                pending.destinations.join(", ")
+            );
+            let change = ServiceChange {
+                name: name.clone(),
+                from: pending.from,
+                to: saved.state,
+                message: pending.message.clone(),
+            };
+            let targets = destinations
+                .iter()
+                .filter(|destination| {
+                    destination.services && pending.destinations.contains(&destination.name)
+                })
+                .collect();
+            notices.push((change, targets));
+        }
+
+        let deliveries = notices
+            .iter()
+            .enumerate()
+            .flat_map(|(notice, (change, targets))| {
+                targets.iter().map(move |destination| {
+                    let notification = render::service(change, destination.locale);
+                    async move {
+                        let delivery = destination.deliver(&notification, None).await;
+                        (notice, destination.name.clone(), delivery.ok)
+                    }
+                })
+            });
+        let mut missed: Vec<Vec<String>> = notices.iter().map(|_| Vec::new()).collect();
+        for (notice, name, ok) in join_all(deliveries).await {
+            if !ok {
+                missed[notice].push(name);
+            }
+        }
+
+        for ((change, _), missed) in notices.into_iter().zip(missed) {
+            if let Some(saved) = self.states.get_mut(&change.name) {
+                saved.pending = (!missed.is_empty()).then(|| PendingChange {
+                    from: change.from,
+                    message: change.message,
+                    destinations: missed,
+                });
+            }
+        }
+
+        self.states != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use anyhow::{bail, Result};
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::{
+        config::{DestinationConfig, NotifierKind},
+        models::Event,
+        notify::{Notification, Notifier},
+        status::StatusError,
+    };
+
+    fn service(name: &str, status: ServiceState) -> ServiceStatus {
+        ServiceStatus {
+            name: name.to_string(),
+            status,
+        }
+    }
+
+    fn global(status: ServiceState, message: Option<&str>) -> GlobalStatus {
+        GlobalStatus {
+            status,
+            message: message.map(str::to_string),
+        }
+    }
+
+    fn operational() -> GlobalStatus {
+        global(ServiceState::Operational, None)
+    }
+
+    #[test]
+    fn first_states_are_only_recorded() {
+        let mut monitor = ServiceMonitor::default();
+
+        let changes = monitor.update(
+            &[service("Launcher", ServiceState::Down)],
+            &global(ServiceState::Degraded, None),
+        );
+
+        assert!(changes.is_empty());
+        assert_eq!(monitor.states()["Launcher"].state, ServiceState::Down);
+        assert_eq!(monitor.states()[GLOBAL_NAME].state, ServiceState::Degraded);
+    }
+
+    #[test]
+    fn reports_transitions() {
+        let mut monitor = ServiceMonitor::default();
+        monitor.update(
+            &[service("Launcher", ServiceState::Operational)],
+            &operational(),
+        );
+
+        let changes = monitor.update(&[service("Launcher", ServiceState::Down)], &operational());
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].name, "Launcher");
+        assert_eq!(changes[0].from, ServiceState::Operational);
+        assert_eq!(changes[0].to, ServiceState::Down);
+        assert_eq!(changes[0].message, None);
+        assert!(monitor
+            .update(&[service("Launcher", ServiceState::Down)], &operational())
+            .is_empty());
+    }
+
+    #[test]
+    fn reports_the_global_status_with_its_message() {
+        let mut monitor = ServiceMonitor::default();
+        monitor.update(&[], &operational());
+
+        let changes = monitor.update(
+            &[],
+            &global(ServiceState::Updating, Some("Technical works")),
+        );
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].name, GLOBAL_NAME);
+        assert_eq!(changes[0].to, ServiceState::Updating);
+        assert_eq!(changes[0].message.as_deref(), Some("Technical works"));
+    }
+
+    #[test]
+    fn unknown_states_are_skipped() {
+        let mut monitor = ServiceMonitor::default();
+        monitor.update(
+            &[service("Launcher", ServiceState::Operational)],
+            &operational(),
+        );
+
+        let changes = monitor.update(
+            &[service("Launcher", ServiceState::Unknown)],
+            &operational(),
+        );
+        assert!(changes.is_empty());
+        assert_eq!(
+            monitor.states()["Launcher"].state,
+            ServiceState::Operational
+        );
+
+        monitor.update(&[service("Trading", ServiceState::Unknown)], &operational());
+        assert!(!monitor.states().contains_key("Trading"));
+    }
+
+    // Answers each poll with the next list of services, then keeps answering with the last one.
+    struct Services(Mutex<VecDeque<Vec<ServiceStatus>>>);
+
+    #[async_trait]
+    impl StatusSource for Services {
+        async fn messages(&self) -> Result<Vec<Event>, StatusError> {
+            Ok(Vec::new())
+        }
+
+        async fn service_statuses(
+            &self,
+        ) -> Result<Option<(Vec<ServiceStatus>, GlobalStatus)>, StatusError> {
+            let mut script = self.0.lock().unwrap();
+            let services = match script.len() {
+                1 => script[0].clone(),
+                _ => script.pop_front().unwrap(),
+            };
+            Ok(Some((services, operational())))
+        }
+    }
+
+    // Fails the first `failures` sends, then records the titles of what it gets.
+    struct Flaky {
+        failures: Mutex<u32>,
+        sent: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Notifier for Flaky {
+        async fn send(&self, notification: &Notification) -> Result<Option<String>> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                bail!("unreachable");
+            }
+            self.sent.lock().unwrap().push(notification.title.clone());
+            Ok(None)
+        }
+    }
+
+    fn destination(
+        name: &str,
+        failures: u32,
+        services: bool,
+    ) -> (Destination, Arc<Mutex<Vec<String>>>) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let notifier = Flaky {
+            failures: Mutex::new(failures),
+            sent: sent.clone(),
+        };
+        let config = DestinationConfig {
+            services: Some(services),
+            ..DestinationConfig::new(name, NotifierKind::Json)
+        };
+        let policy = RetryPolicy {
+            max_attempts: 1,
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+        };
+        let destination = Destination::new(&config, "EN", Box::new(notifier), policy).unwrap();
+
+        (destination, sent)
+    }
+
+    #[tokio::test]
+    async fn missed_transitions_are_retried() {
+        let source = Services(Mutex::new(VecDeque::from([
+            vec![service("Launcher", ServiceState::Operational)],
+            vec![service("Launcher", ServiceState::Down)],
+        ])));
+        let policy = RetryPolicy {
+            max_attempts: 1,
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+        };
+        let (flaky, flaky_sent) = destination("flaky", 1, true);
+        let (steady, steady_sent) = destination("steady", 0, true);
+        let (muted, muted_sent) = destination("muted", 0, false);
+        let destinations = [flaky, steady, muted];
+        let mut monitor = ServiceMonitor::default();
+
+        // First seen, nothing to say but something to save
+        assert!(monitor.poll(&source, &policy, &destinations).await);
+        assert!(steady_sent.lock().unwrap().is_empty());
+
+        // Only the flaky destination missed the transition
+        assert!(monitor.poll(&source, &policy, &destinations).await);
+        assert_eq!(steady_sent.lock().unwrap().len(), 1);
+        assert!(flaky_sent.lock().unwrap().is_empty());
+        let pending = monitor.states()["Launcher"].pending.clone().unwrap();
+        assert_eq!(pending.from, ServiceState::Operational);
+        assert_eq!(pending.destinations, ["flaky"]);
+
+        // Same state, the transition goes to the flaky destination alone
+        assert!(monitor.poll(&source, &policy, &destinations).await);
+        assert_eq!(*flaky_sent.lock().unwrap(), *steady_sent.lock().unwrap());
+        assert_eq!(steady_sent.lock().unwrap().len(), 1);
+        assert_eq!(monitor.states()["Launcher"].pending, None);
+
+        assert!(!monitor.poll(&source, &policy, &destinations).await);
+        assert!(muted_sent.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use std::{collections::VecDeque, fs, io, path::PathBuf, sync::Mutex};
+
+use async_trait::async_trait;
+use futures::try_join;
+
+use crate::{
+    models::{Event, GlobalStatus, ServiceStatus},
+    retry::RetryPolicy,
+    status::{StatusClient, StatusError},
+};
+
+/// Where the events come from: the status API, or a fixture so the pipeline can run offline.
+#[async_trait]
+pub trait StatusSource: Send + Sync {
+    /// Messages currently listed on the status page.
+    async fn messages(&self) -> Result<Vec<Event>, StatusError>;
+
+    /// Health of each service and the global status, None when the source only has the messages.
+    async fn service_statuses(
+        &self,
+    ) -> Result<Option<(Vec<ServiceStatus>, GlobalStatus)>, StatusError> {
+        Ok(None)
+    }
+
+    /// Same as `messages`, retrying transient failures according to `policy`.
+    /// An error means the API is down, which is not the same as an empty list.
+    async fn messages_with_retry(&self, policy: &RetryPolicy) -> Result<Vec<Event>, StatusError> {
+        policy.run("Status API poll", || self.messages()).await
+    }
+}
+
+#[async_trait]
+impl StatusSource for StatusClient {
+    async fn messages(&self) -> Result<Vec<Event>, StatusError> {
+        StatusClient::messages(self).await
+    }
+
+    async fn service_statuses(
+        &self,
+    ) -> Result<Option<(Vec<ServiceStatus>, GlobalStatus)>, StatusError> {
+        let (services, global) = try_join!(self.services(), self.global_status())?;
+        Ok(Some((services, global)))
+    }
+}
+
+/// Reads a captured `/api/message/list` response on every poll.
+pub struct FileSource {
+    path: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait]
+impl StatusSource for FileSource {
+    async fn messages(&self) -> Result<Vec<Event>, StatusError> {
+        let file_error = |error| StatusError::File {
+            path: self.path.clone(),
+            error,
+        };
+        let content = fs::read_to_string(&self.path).map_err(file_error)?;
+
+        // Unlike a response, a broken file won't get any better by asking again
+        serde_json::from_str(&content)
+            .map_err(|error| file_error(io::Error::new(io::ErrorKind::InvalidData, error)))
+    }
+}
+
+/// Answers each call with the next snapshot (a list of events, or an outage), then keeps
+/// answering with the last list once they are all used up.
+pub struct ScriptedSource {
+    script: Mutex<Script>,
+}
+
+struct Script {
+    snapshots: VecDeque<Result<Vec<Event>, StatusError>>,
+    last: Vec<Event>,
+}
+
+impl ScriptedSource {
+    pub fn new(snapshots: impl IntoIterator<Item = Result<Vec<Event>, StatusError>>) -> Self {
+        Self {
+            script: Mutex::new(Script {
+                snapshots: snapshots.into_iter().collect(),
+                last: Vec::new(),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl StatusSource for ScriptedSource {
+    async fn messages(&self) -> Result<Vec<Event>, StatusError> {
+        let mut script = self.script.lock().unwrap();
+        match script.snapshots.pop_front() {
+            Some(Ok(events)) => {
+                script.last = events.clone();
+                Ok(events)
+            }
+            Some(Err(e)) => Err(e),
+            None => Ok(script.last.clone()),
+        }
+    }
+}
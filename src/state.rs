@@ -0,0 +1,448 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{StateBackend, StateConfig},
+    maintenance::Schedule,
+    models::{Event, EventType, ServiceState},
+};
+
+// What we remember about an event we already posted, keyed by event id.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct SavedEvent {
+    pub content: String,
+    pub event_type: EventType,
+    pub solve_time: Option<DateTime<Utc>>,
+    // Id of the message tracking the event, keyed by destination name.
+    pub messages: HashMap<String, String>,
+    // Destinations the current version of the event couldn't be delivered to, tried again on the next poll.
+    pub pending: Vec<String>,
+    // Times the content of the event changed after it was first posted.
+    pub revisions: u32,
+    // Successful polls in a row the event was missing from, reset when it shows up again.
+    pub missing_polls: u32,
+    pub missing_since: Option<DateTime<Utc>>,
+    // Maintenance window announced by the event, to send reminders about.
+    pub maintenance: Option<Schedule>,
+}
+
+impl SavedEvent {
+    pub fn changes(&self, event: &Event) -> EventChanges {
+        EventChanges {
+            content: self.content != event.content,
+            event_type: self.event_type != event.event_type,
+            solve_time: self.solve_time != event.solve_time,
+        }
+    }
+}
+
+pub type SavedEvents = HashMap<String, SavedEvent>;
+
+// Last state seen of a service.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SavedService {
+    pub state: ServiceState,
+    // The transition to `state`, while some destinations still have to get it.
+    #[serde(default)]
+    pub pending: Option<PendingChange>,
+}
+
+// A service transition that couldn't be delivered everywhere, tried again on the next poll.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PendingChange {
+    pub from: ServiceState,
+    pub message: Option<String>,
+    pub destinations: Vec<String>,
+}
+
+// Keyed by service name.
+pub type ServiceStates = HashMap<String, SavedService>;
+
+// Everything kept across restarts.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct State {
+    pub events: SavedEvents,
+    // So that a service changing between two runs (e.g. under cron) is still reported.
+    #[serde(default)]
+    pub services: ServiceStates,
+}
+
+// When events missing from the fetched list are forgotten, whichever of the two comes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForgetPolicy {
+    // Consecutive successful polls an event must be missing from.
+    pub polls: u32,
+    pub after: Option<chrono::Duration>,
+}
+
+impl From<&StateConfig> for ForgetPolicy {
+    fn from(config: &StateConfig) -> Self {
+        Self {
+            polls: config.forget_after_polls,
+            after: config
+                .forget_after_minutes
+                .map(|minutes| chrono::Duration::minutes(minutes as i64)),
+        }
+    }
+}
+
+impl Default for ForgetPolicy {
+    fn default() -> Self {
+        Self::from(&StateConfig::default())
+    }
+}
+
+// Updates the missing counters against a successfully fetched list, then forgets the events missing
+// according to `policy`.
+// A single poll where the API hides an event doesn't lose its messages and re-post it later.
+// Returns whether anything changed.
+pub fn forget_missing(
+    saved_events: &mut SavedEvents,
+    events: &[Event],
+    policy: &ForgetPolicy,
+    now: DateTime<Utc>,
+) -> bool {
+    let mut changed = false;
+
+    saved_events.retain(|id, saved_event| {
+        if events.iter().any(|event| event.id == *id) {
+            if saved_event.missing_polls > 0 {
+                saved_event.missing_polls = 0;
+                saved_event.missing_since = None;
+                changed = true;
+            }
+            return true;
+        }
+
+        saved_event.missing_polls += 1;
+        let missing_since = *saved_event.missing_since.get_or_insert(now);
+        changed = true;
+
+        let expired = saved_event.missing_polls >= policy.polls
+            || policy
+                .after
+                .is_some_and(|after| now - missing_since >= after);
+        if expired {
+            debug!(
+                "Forgetting event {id}, missing for {} polls",
+                saved_event.missing_polls
+            );
+        }
+
+        !expired
+    });
+
+    changed
+}
+
+// Which of the fields we post changed since the last time an event was sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventChanges {
+    pub content: bool,
+    pub event_type: bool,
+    pub solve_time: bool,
+}
+
+impl EventChanges {
+    pub fn any(&self) -> bool {
+        self.content || self.event_type || self.solve_time
+    }
+}
+
+impl fmt::Display for EventChanges {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields: Vec<&str> = [
+            (self.content, "content"),
+            (self.event_type, "type"),
+            (self.solve_time, "solve time"),
+        ]
+        .into_iter()
+        .filter_map(|(changed, name)| changed.then_some(name))
+        .collect();
+
+        write!(f, "{}", fields.join(", "))
+    }
+}
+
+pub trait StateStore {
+    fn load(&self) -> Result<State>;
+    fn save(&self, events: &SavedEvents, services: &ServiceStates) -> Result<()>;
+}
+
+pub fn open(config: &StateConfig) -> Result<Box<dyn StateStore>> {
+    let store: Box<dyn StateStore> = match config.backend {
+        StateBackend::Memory => Box::new(MemoryStore),
+        StateBackend::Json => Box::new(JsonFileStore::new(config.path())),
+        #[cfg(feature = "sqlite")]
+        StateBackend::Sqlite => Box::new(sqlite::SqliteStore::open(&config.path())?),
+        #[cfg(not(feature = "sqlite"))]
+        StateBackend::Sqlite => anyhow::bail!("Built without the `sqlite` feature"),
+    };
+
+    Ok(store)
+}
+
+// Keeps nothing, every restart starts from scratch.
+pub struct MemoryStore;
+
+impl StateStore for MemoryStore {
+    fn load(&self) -> Result<State> {
+        Ok(State::default())
+    }
+
+    fn save(&self, _events: &SavedEvents, _services: &ServiceStates) -> Result<()> {
+        Ok(())
+    }
+}
+
+// Borrowed counterpart of `State`, to save without cloning.
+#[derive(Serialize)]
+struct StateFile<'a> {
+    events: &'a SavedEvents,
+    services: &'a ServiceStates,
+}
+
+pub struct JsonFileStore {
+    path: PathBuf,
+}
+
+impl JsonFileStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl StateStore for JsonFileStore {
+    fn load(&self) -> Result<State> {
+        if !self.path.exists() {
+            return Ok(State::default());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read state file {}", self.path.display()))?;
+        let state: State = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse state file {}", self.path.display()))?;
+
+        Ok(state)
+    }
+
+    fn save(&self, events: &SavedEvents, services: &ServiceStates) -> Result<()> {
+        let content = serde_json::to_vec_pretty(&StateFile { events, services })?;
+
+        write_atomic(&self.path, &content).context("Failed to save state file")
+    }
+}
+
+// Writes next to the real file then renames it, so a crash mid-write never leaves a truncated file behind.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let mut tmp_path = path.to_path_buf().into_os_string();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    write_synced(&tmp_path, content)
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+
+    Ok(())
+}
+
+fn write_synced(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(content)?;
+    file.sync_all()
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use std::path::Path;
+
+    use anyhow::{Context, Result};
+    use rusqlite::{params, Connection};
+
+    use super::{SavedEvents, ServiceStates, State, StateStore};
+
+    // Each row holds the JSON encoded `SavedEvent` or `SavedService`, so new fields don't need a schema migration.
+    pub struct SqliteStore {
+        conn: Connection,
+    }
+
+    impl SqliteStore {
+        pub fn open(path: &Path) -> Result<Self> {
+            let conn = Connection::open(path)
+                .with_context(|| format!("Failed to open state database {}", path.display()))?;
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS events (id TEXT PRIMARY KEY NOT NULL, data TEXT NOT NULL);
+                 CREATE TABLE IF NOT EXISTS services (name TEXT PRIMARY KEY NOT NULL, data TEXT NOT NULL);",
+            )?;
+
+            Ok(Self { conn })
+        }
+    }
+
+    impl StateStore for SqliteStore {
+        fn load(&self) -> Result<State> {
+            let mut stmt = self.conn.prepare("SELECT id, data FROM events")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+
+            let mut events = SavedEvents::new();
+            for row in rows {
+                let (id, data) = row?;
+                let event = serde_json::from_str(&data)
+                    .with_context(|| format!("Failed to parse saved event {id}"))?;
+                events.insert(id, event);
+            }
+
+            let mut stmt = self.conn.prepare("SELECT name, data FROM services")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+
+            let mut services = ServiceStates::new();
+            for row in rows {
+                let (name, data) = row?;
+                let service = serde_json::from_str(&data)
+                    .with_context(|| format!("Failed to parse saved service {name}"))?;
+                services.insert(name, service);
+            }
+
+            Ok(State { events, services })
+        }
+
+        fn save(&self, events: &SavedEvents, services: &ServiceStates) -> Result<()> {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute("DELETE FROM events", [])?;
+            tx.execute("DELETE FROM services", [])?;
+            {
+                let mut stmt = tx.prepare("INSERT INTO events (id, data) VALUES (?1, ?2)")?;
+                for (id, event) in events {
+                    stmt.execute(params![id, serde_json::to_string(event)?])?;
+                }
+                let mut stmt = tx.prepare("INSERT INTO services (name, data) VALUES (?1, ?2)")?;
+                for (name, service) in services {
+                    stmt.execute(params![name, serde_json::to_string(service)?])?;
+                }
+            }
+            tx.commit()?;
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    fn event(id: &str) -> Event {
+        Event {
+            id: id.to_string(),
+            content: "Servers are down".to_string(),
+            event_type: EventType::ServerIssues,
+            time: Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap(),
+            solve_time: None,
+            start_time: None,
+            end_time: None,
+        }
+    }
+
+    fn saved(ids: &[&str]) -> SavedEvents {
+        ids.iter()
+            .map(|id| (id.to_string(), SavedEvent::default()))
+            .collect()
+    }
+
+    fn policy(polls: u32, minutes: Option<i64>) -> ForgetPolicy {
+        ForgetPolicy {
+            polls,
+            after: minutes.map(Duration::minutes),
+        }
+    }
+
+    #[test]
+    fn listed_events_are_kept_untouched() {
+        let mut saved_events = saved(&["a"]);
+        let now = Utc::now();
+
+        let changed = forget_missing(&mut saved_events, &[event("a")], &policy(1, None), now);
+
+        assert!(!changed);
+        assert_eq!(saved_events["a"].missing_polls, 0);
+    }
+
+    #[test]
+    fn missing_events_are_forgotten_after_enough_polls() {
+        let mut saved_events = saved(&["a", "b"]);
+        let policy = policy(3, None);
+        let listed = [event("a")];
+        let now = Utc::now();
+
+        for poll in 1..3 {
+            assert!(forget_missing(&mut saved_events, &listed, &policy, now));
+            assert_eq!(saved_events["b"].missing_polls, poll);
+            assert_eq!(saved_events["b"].missing_since, Some(now));
+        }
+        forget_missing(&mut saved_events, &listed, &policy, now);
+
+        assert!(saved_events.contains_key("a"));
+        assert!(!saved_events.contains_key("b"));
+    }
+
+    #[test]
+    fn missing_events_are_forgotten_after_enough_minutes() {
+        let mut saved_events = saved(&["a"]);
+        let policy = policy(100, Some(10));
+        let start = Utc::now();
+
+        forget_missing(&mut saved_events, &[], &policy, start);
+        forget_missing(
+            &mut saved_events,
+            &[],
+            &policy,
+            start + Duration::minutes(9),
+        );
+        assert!(saved_events.contains_key("a"));
+
+        forget_missing(
+            &mut saved_events,
+            &[],
+            &policy,
+            start + Duration::minutes(10),
+        );
+        assert!(saved_events.is_empty());
+    }
+
+    #[test]
+    fn reappearing_events_start_over() {
+        let mut saved_events = saved(&["a"]);
+        let policy = policy(2, None);
+        let now = Utc::now();
+
+        forget_missing(&mut saved_events, &[], &policy, now);
+        assert!(forget_missing(
+            &mut saved_events,
+            &[event("a")],
+            &policy,
+            now
+        ));
+        assert_eq!(saved_events["a"].missing_polls, 0);
+        assert_eq!(saved_events["a"].missing_since, None);
+
+        // Needs two polls in a row again
+        forget_missing(&mut saved_events, &[], &policy, now);
+        assert!(saved_events.contains_key("a"));
+    }
+}
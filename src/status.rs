@@ -0,0 +1,146 @@
+use std::{io, path::PathBuf, time::Duration};
+
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::{
+    http::{body_snippet, retry_after},
+    metrics,
+    models::{Event, GlobalStatus, ServiceStatus},
+    retry::Retryable,
+};
+
+#[derive(Error, Debug)]
+pub enum StatusError {
+    #[error("request to the status API failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("status API returned {status}: {snippet}")]
+    Status {
+        status: StatusCode,
+        snippet: String,
+        retry_after: Option<Duration>,
+    },
+    // e.g. the HTML error page served during outages
+    #[error("unexpected status API response ({error}): {snippet}")]
+    Parse {
+        error: serde_json::Error,
+        snippet: String,
+    },
+    #[error("failed to read {}: {error}", path.display())]
+    File { path: PathBuf, error: io::Error },
+}
+
+impl StatusError {
+    // Metrics label.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Request(_) => "request",
+            Self::Status { .. } => "status",
+            Self::Parse { .. } => "parse",
+            Self::File { .. } => "file",
+        }
+    }
+}
+
+impl Retryable for StatusError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::Request(_) | Self::Parse { .. } => true,
+            Self::Status { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            Self::File { .. } => false,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Status {
+                status,
+                retry_after,
+                ..
+            } if *status == StatusCode::TOO_MANY_REQUESTS
+                || *status == StatusCode::SERVICE_UNAVAILABLE =>
+            {
+                *retry_after
+            }
+            _ => None,
+        }
+    }
+}
+
+// Client for status.escapefromtarkov.com.
+pub struct StatusClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl StatusClient {
+    pub fn new(client: reqwest::Client, base_url: &str) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // Messages currently listed on the status page.
+    pub async fn messages(&self) -> Result<Vec<Event>, StatusError> {
+        self.get("/api/message/list").await
+    }
+
+    // Health of each service (launcher, site, authentication, trading, matching...).
+    pub async fn services(&self) -> Result<Vec<ServiceStatus>, StatusError> {
+        self.get("/api/services").await
+    }
+
+    pub async fn global_status(&self) -> Result<GlobalStatus, StatusError> {
+        self.get("/api/global/status").await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, StatusError> {
+        let metrics = metrics::get();
+        let timer = metrics
+            .status_request_duration
+            .with_label_values(&[path])
+            .start_timer();
+        let result = self.fetch(path).await;
+        timer.observe_duration();
+
+        let outcome = match &result {
+            Ok(_) => "success",
+            Err(e) => e.kind(),
+        };
+        metrics
+            .status_requests
+            .with_label_values(&[path, outcome])
+            .inc();
+
+        result
+    }
+
+    async fn fetch<T: DeserializeOwned>(&self, path: &str) -> Result<T, StatusError> {
+        let resp = self
+            .client
+            .get(format!("{}{path}", self.base_url))
+            .send()
+            .await?;
+
+        let status = resp.status();
+        let retry_after = retry_after(resp.headers());
+        let body = resp.text().await?;
+
+        if !status.is_success() {
+            return Err(StatusError::Status {
+                status,
+                snippet: body_snippet(&body),
+                retry_after,
+            });
+        }
+
+        serde_json::from_str(&body).map_err(|error| StatusError::Parse {
+            error,
+            snippet: body_snippet(&body),
+        })
+    }
+}
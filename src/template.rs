@@ -0,0 +1,330 @@
+use anyhow::{Context, Result};
+use handlebars::{no_escape, Handlebars};
+use serde_json::{json, Value};
+
+use crate::{
+    config::{parse_color, TemplateConfig},
+    locale::Locale,
+    notify::{Field, Notification},
+    render::format_duration,
+};
+
+// Handlebars templates of a destination, each one replacing a part of the default notification.
+// Templates see the default notification (`title`, `description`, `fields`...), the event, the translated
+// `content`, the `duration` of resolved events and every `locale` string.
+// Only incidents are templated: service changes, maintenance reminders and the shutdown notice have no event
+// to fill them with, so they keep the default layout.
+pub struct Template {
+    registry: Handlebars<'static>,
+    // Inline flag of each field template.
+    fields: Vec<bool>,
+}
+
+impl Template {
+    pub fn new(config: &TemplateConfig) -> Result<Self> {
+        let mut registry = Handlebars::new();
+        // Chat messages aren't HTML, notifiers escape what they need themselves
+        registry.register_escape_fn(no_escape);
+
+        let parts = [
+            ("title", &config.title),
+            ("description", &config.description),
+            ("color", &config.color),
+            ("thumbnail", &config.thumbnail),
+            ("url", &config.url),
+        ];
+        for (name, template) in parts {
+            if let Some(template) = template {
+                registry
+                    .register_template_string(name, template)
+                    .with_context(|| format!("Invalid {name} template"))?;
+            }
+        }
+        for (i, field) in config.fields.iter().enumerate() {
+            registry
+                .register_template_string(&format!("field{i}.name"), &field.name)
+                .with_context(|| format!("Invalid name template of field {}", i + 1))?;
+            registry
+                .register_template_string(&format!("field{i}.value"), &field.value)
+                .with_context(|| format!("Invalid value template of field {}", i + 1))?;
+        }
+
+        Ok(Self {
+            registry,
+            fields: config.fields.iter().map(|field| field.inline).collect(),
+        })
+    }
+
+    pub fn render(&self, notification: &Notification, locale: &Locale) -> Result<Notification> {
+        if notification.event.is_none() {
+            return Ok(notification.clone());
+        }
+
+        let data = context(notification, locale);
+        let render = |name: &str| -> Result<Option<String>> {
+            if !self.registry.has_template(name) {
+                return Ok(None);
+            }
+            let rendered = self
+                .registry
+                .render(name, &data)
+                .with_context(|| format!("Failed to render the {name} template"))?;
+            Ok(Some(rendered))
+        };
+
+        let mut rendered = notification.clone();
+        if let Some(title) = render("title")? {
+            rendered.title = title;
+        }
+        if let Some(description) = render("description")? {
+            rendered.description = description;
+        }
+        if let Some(color) = render("color")? {
+            let color = color.trim();
+            let parsed = match color.strip_prefix('#') {
+                Some(_) => parse_color(color),
+                None => color.parse().ok(),
+            };
+            rendered.color = Some(
+                parsed.with_context(|| format!("Invalid color \"{color}\" from the template"))?,
+            );
+        }
+        // An empty result removes the thumbnail or link
+        if let Some(thumbnail) = render("thumbnail")? {
+            rendered.thumbnail = Some(thumbnail).filter(|thumbnail| !thumbnail.is_empty());
+        }
+        if let Some(url) = render("url")? {
+            rendered.url = Some(url).filter(|url| !url.is_empty());
+        }
+        if !self.fields.is_empty() {
+            rendered.fields = Vec::new();
+            for (i, inline) in self.fields.iter().enumerate() {
+                let name = render(&format!("field{i}.name"))?.unwrap_or_default();
+                let value = render(&format!("field{i}.value"))?.unwrap_or_default();
+                rendered.fields.push(Field::text(&name, &value, *inline));
+            }
+        }
+
+        Ok(rendered)
+    }
+
+    // A template failing to render falls back to the default layout rather than losing the message.
+    pub fn render_or_default(
+        &self,
+        notification: &Notification,
+        locale: &Locale,
+        destination: &str,
+    ) -> Notification {
+        self.render(notification, locale).unwrap_or_else(|e| {
+            warn!("[{destination}] {e:#}, using the default layout");
+            notification.clone()
+        })
+    }
+}
+
+fn context(notification: &Notification, locale: &Locale) -> Value {
+    let mut data = serde_json::to_value(notification).unwrap_or_else(|_| json!({}));
+    data["content"] = json!(notification.description);
+    data["locale"] = serde_json::to_value(locale).unwrap_or_default();
+
+    // Unix timestamps too, e.g. for Discord's `<t:{{event.timestamp}}:R>`
+    if let Some(event) = &notification.event {
+        data["event"]["type_name"] = json!(event.event_type.to_string());
+        data["event"]["timestamp"] = json!(event.time.timestamp());
+        if let Some(solve_time) = event.solve_time {
+            data["event"]["solve_timestamp"] = json!(solve_time.timestamp());
+            data["duration"] = json!(format_duration(solve_time - event.time, locale));
+        }
+    }
+
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::{
+        config::FieldTemplateConfig,
+        locale,
+        models::{Event, EventType},
+        render::{self, History},
+    };
+
+    fn incident(solved: bool) -> Notification {
+        let event = Event {
+            id: "incident-1".to_string(),
+            content: "Servers are down".to_string(),
+            event_type: EventType::ServerIssues,
+            time: Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap(),
+            solve_time: solved.then(|| Utc.with_ymd_and_hms(2024, 1, 1, 11, 30, 0).unwrap()),
+            start_time: None,
+            end_time: None,
+        };
+        render::incident(
+            &event,
+            "Servers are down",
+            &locale::EN,
+            None,
+            &History { revisions: 0 },
+        )
+    }
+
+    fn template(config: TemplateConfig) -> Template {
+        Template::new(&config).unwrap()
+    }
+
+    fn color(template_color: &str) -> Template {
+        template(TemplateConfig {
+            color: Some(template_color.to_string()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn renders_the_parts_it_has() {
+        let default = incident(false);
+        let template = template(TemplateConfig {
+            title: Some("[{{event.type_name}}] {{title}}".to_string()),
+            description: Some("{{content}} <t:{{event.timestamp}}:R>".to_string()),
+            ..Default::default()
+        });
+
+        let rendered = template.render(&default, &locale::EN).unwrap();
+
+        assert_eq!(rendered.title, format!("[ServerIssues] {}", default.title));
+        assert_eq!(rendered.description, "Servers are down <t:1704103200:R>");
+        assert_eq!(rendered.url, default.url);
+        assert_eq!(rendered.thumbnail, default.thumbnail);
+        assert_eq!(rendered.fields.len(), default.fields.len());
+    }
+
+    #[test]
+    fn resolved_events_have_a_duration() {
+        let template = template(TemplateConfig {
+            description: Some("{{#if duration}}{{duration}}{{else}}-{{/if}}".to_string()),
+            ..Default::default()
+        });
+
+        let ongoing = template.render(&incident(false), &locale::EN).unwrap();
+        let resolved = template.render(&incident(true), &locale::EN).unwrap();
+
+        assert_eq!(ongoing.description, "-");
+        assert_eq!(resolved.description, "1 h 30 min");
+    }
+
+    #[test]
+    fn parses_hex_and_decimal_colors() {
+        let hex = color("{{#if event.solve_time}}#2ecc71{{else}}#e74c3c{{/if}}");
+        assert_eq!(
+            hex.render(&incident(false), &locale::EN).unwrap().color,
+            Some(0xe74c3c)
+        );
+        assert_eq!(
+            hex.render(&incident(true), &locale::EN).unwrap().color,
+            Some(0x2ecc71)
+        );
+
+        let decimal = color(" 3066993 ");
+        assert_eq!(
+            decimal.render(&incident(false), &locale::EN).unwrap().color,
+            Some(3066993)
+        );
+
+        assert!(color("red").render(&incident(false), &locale::EN).is_err());
+        assert!(color("#12345")
+            .render(&incident(false), &locale::EN)
+            .is_err());
+    }
+
+    #[test]
+    fn empty_thumbnail_and_url_are_removed() {
+        let template = template(TemplateConfig {
+            thumbnail: Some("".to_string()),
+            url: Some("{{#if event.solve_time}}https://example.com{{/if}}".to_string()),
+            ..Default::default()
+        });
+
+        let rendered = template.render(&incident(false), &locale::EN).unwrap();
+
+        assert_eq!(rendered.thumbnail, None);
+        assert_eq!(rendered.url, None);
+    }
+
+    #[test]
+    fn fields_replace_every_default_field() {
+        let template = template(TemplateConfig {
+            fields: vec![
+                FieldTemplateConfig {
+                    name: "{{locale.since}}".to_string(),
+                    value: "<t:{{event.timestamp}}:R>".to_string(),
+                    inline: true,
+                },
+                FieldTemplateConfig {
+                    name: "Id".to_string(),
+                    value: "{{event.id}}".to_string(),
+                    inline: false,
+                },
+            ],
+            ..Default::default()
+        });
+
+        let rendered = template.render(&incident(true), &locale::EN).unwrap();
+
+        let fields: Vec<(&str, String, bool)> = rendered
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.value.to_text(), field.inline))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("Since", "<t:1704103200:R>".to_string(), true),
+                ("Id", "incident-1".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_default_layout() {
+        let default = incident(false);
+        let template = template(TemplateConfig {
+            title: Some("Custom".to_string()),
+            color: Some("not a color".to_string()),
+            ..Default::default()
+        });
+
+        let rendered = template.render_or_default(&default, &locale::EN, "discord");
+
+        assert_eq!(rendered.title, default.title);
+        assert_eq!(rendered.color, default.color);
+    }
+
+    #[test]
+    fn other_notifications_keep_the_default_layout() {
+        let template = template(TemplateConfig {
+            title: Some("[{{event.type_name}}] {{title}}".to_string()),
+            color: Some("{{#if event.solve_time}}#2ecc71{{else}}#e74c3c{{/if}}".to_string()),
+            ..Default::default()
+        });
+        let offline = render::offline(&locale::EN);
+
+        let rendered = template.render(&offline, &locale::EN).unwrap();
+
+        assert_eq!(rendered.title, offline.title);
+        assert_eq!(rendered.color, None);
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        let e = Template::new(&TemplateConfig {
+            title: Some("{{#if event.solve_time}}unclosed".to_string()),
+            ..Default::default()
+        })
+        .err()
+        .unwrap();
+
+        assert!(e.to_string().contains("title"));
+    }
+}
@@ -0,0 +1,294 @@
+use std::{
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{TranslateError, Translator};
+use crate::state::write_atomic;
+
+// Backends detect the source language themselves.
+const SOURCE_LANG: &str = "auto";
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheFile {
+    // least recently used first
+    entries: Vec<(String, String)>,
+}
+
+impl From<&LruCache<String, String>> for CacheFile {
+    fn from(entries: &LruCache<String, String>) -> Self {
+        // iter() goes from the most to the least recently used entry
+        Self {
+            entries: entries
+                .iter()
+                .rev()
+                .map(|(key, translation)| (key.clone(), translation.clone()))
+                .collect(),
+        }
+    }
+}
+
+// Wraps another translator so a given text is only translated once per target language.
+pub struct CachedTranslator {
+    inner: Box<dyn Translator>,
+    entries: Mutex<LruCache<String, String>>,
+    path: Option<PathBuf>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    saving: tokio::sync::Mutex<()>,
+}
+
+impl CachedTranslator {
+    pub fn new(inner: Box<dyn Translator>, size: NonZeroUsize, path: Option<PathBuf>) -> Self {
+        let mut entries = LruCache::new(size);
+
+        if let Some(path) = &path {
+            match load(path) {
+                Ok(loaded) => {
+                    for (key, translation) in loaded {
+                        entries.put(key, translation);
+                    }
+                    info!("Loaded {} cached translations", entries.len());
+                }
+                Err(e) => warn!("Ignoring translation cache: {e:#}"),
+            }
+        }
+
+        Self {
+            inner,
+            entries: Mutex::new(entries),
+            path,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            saving: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    // Writes on a blocking thread, one save at a time so an older snapshot can't replace a newer one.
+    async fn save(&self, path: PathBuf, file: CacheFile) {
+        let _saving = self.saving.lock().await;
+        let saved = tokio::task::spawn_blocking(move || save(&path, &file)).await;
+        match saved {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Failed to save translation cache: {e:#}"),
+            Err(e) => warn!("Failed to save translation cache: {e}"),
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for CachedTranslator {
+    async fn translate(&self, text: &str, target_lang: &str) -> Result<String, TranslateError> {
+        let key = cache_key(text, target_lang);
+
+        let cached = self.entries.lock().unwrap().get(&key).cloned();
+        if let Some(translation) = cached {
+            let hits = self.hits.fetch_add(1, Ordering::Relaxed) + 1;
+            debug!(
+                "Translation cache hit for {key} ({hits} hits, {} misses)",
+                self.misses.load(Ordering::Relaxed)
+            );
+            return Ok(translation);
+        }
+
+        let misses = self.misses.fetch_add(1, Ordering::Relaxed) + 1;
+        debug!(
+            "Translation cache miss for {key} ({} hits, {misses} misses)",
+            self.hits.load(Ordering::Relaxed)
+        );
+
+        let translation = self.inner.translate(text, target_lang).await?;
+
+        let file = {
+            let mut entries = self.entries.lock().unwrap();
+            entries.put(key, translation.clone());
+            self.path.as_ref().map(|_| CacheFile::from(&*entries))
+        };
+        if let (Some(path), Some(file)) = (&self.path, file) {
+            self.save(path.clone(), file).await;
+        }
+
+        Ok(translation)
+    }
+}
+
+fn cache_key(text: &str, target_lang: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(SOURCE_LANG.as_bytes());
+    hasher.update([0]);
+    hasher.update(target_lang.to_uppercase().as_bytes());
+    hasher.update([0]);
+    hasher.update(text.as_bytes());
+
+    format!("{:x}", hasher.finalize())
+}
+
+fn load(path: &Path) -> Result<Vec<(String, String)>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: CacheFile = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    Ok(file.entries)
+}
+
+fn save(path: &Path, file: &CacheFile) -> Result<()> {
+    write_atomic(path, &serde_json::to_vec(file)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    // Appends the target language, fails on "fail" and records the calls it gets.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Translator for Recorder {
+        async fn translate(&self, text: &str, target_lang: &str) -> Result<String, TranslateError> {
+            self.calls.lock().unwrap().push(text.to_string());
+            if text == "fail" {
+                return Err(TranslateError::Empty);
+            }
+
+            Ok(format!("{text} ({target_lang})"))
+        }
+    }
+
+    fn cached(recorder: &Recorder, size: usize, path: Option<PathBuf>) -> CachedTranslator {
+        CachedTranslator::new(
+            Box::new(recorder.clone()),
+            NonZeroUsize::new(size).unwrap(),
+            path,
+        )
+    }
+
+    #[tokio::test]
+    async fn only_misses_reach_the_inner_translator() {
+        let recorder = Recorder::default();
+        let translator = cached(&recorder, 10, None);
+
+        assert_eq!(translator.translate("a", "FR").await.unwrap(), "a (FR)");
+        assert_eq!(translator.translate("a", "FR").await.unwrap(), "a (FR)");
+        assert_eq!(translator.translate("b", "FR").await.unwrap(), "b (FR)");
+
+        assert_eq!(recorder.calls(), ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn keys_ignore_the_case_of_the_target_language() {
+        let recorder = Recorder::default();
+        let translator = cached(&recorder, 10, None);
+
+        translator.translate("a", "EN-GB").await.unwrap();
+        assert_eq!(
+            translator.translate("a", "en-gb").await.unwrap(),
+            "a (EN-GB)"
+        );
+        translator.translate("a", "DE").await.unwrap();
+
+        assert_eq!(recorder.calls(), ["a", "a"]);
+    }
+
+    #[tokio::test]
+    async fn failed_translations_are_not_cached() {
+        let recorder = Recorder::default();
+        let translator = cached(&recorder, 10, None);
+
+        assert!(translator.translate("fail", "FR").await.is_err());
+        assert!(translator.translate("fail", "FR").await.is_err());
+
+        assert_eq!(recorder.calls(), ["fail", "fail"]);
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_entries_are_evicted() {
+        let recorder = Recorder::default();
+        let translator = cached(&recorder, 2, None);
+
+        translator.translate("a", "FR").await.unwrap();
+        translator.translate("b", "FR").await.unwrap();
+        // "a" becomes the most recently used, so "c" evicts "b"
+        translator.translate("a", "FR").await.unwrap();
+        translator.translate("c", "FR").await.unwrap();
+        translator.translate("a", "FR").await.unwrap();
+        translator.translate("b", "FR").await.unwrap();
+
+        assert_eq!(recorder.calls(), ["a", "b", "c", "b"]);
+    }
+
+    #[tokio::test]
+    async fn entries_are_saved_least_recently_used_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("translations.json");
+        let translator = cached(&Recorder::default(), 10, Some(path.clone()));
+
+        translator.translate("a", "FR").await.unwrap();
+        translator.translate("b", "FR").await.unwrap();
+        translator.translate("a", "FR").await.unwrap();
+        translator.translate("c", "FR").await.unwrap();
+
+        let file: CacheFile = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        let expected = [("b", "b (FR)"), ("a", "a (FR)"), ("c", "c (FR)")]
+            .map(|(text, translation)| (cache_key(text, "FR"), translation.to_string()));
+        assert_eq!(file.entries, expected);
+    }
+
+    #[tokio::test]
+    async fn saved_entries_are_reloaded_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("translations.json");
+        let translator = cached(&Recorder::default(), 10, Some(path.clone()));
+        translator.translate("a", "FR").await.unwrap();
+        translator.translate("b", "FR").await.unwrap();
+        translator.translate("a", "FR").await.unwrap();
+
+        // A smaller cache only keeps the most recently used entry
+        let recorder = Recorder::default();
+        let reloaded = cached(&recorder, 1, Some(path));
+        assert_eq!(reloaded.translate("a", "FR").await.unwrap(), "a (FR)");
+        reloaded.translate("b", "FR").await.unwrap();
+
+        assert_eq!(recorder.calls(), ["b"]);
+    }
+
+    #[tokio::test]
+    async fn unreadable_cache_files_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("translations.json");
+        fs::write(&path, "not json").unwrap();
+
+        let recorder = Recorder::default();
+        let translator = cached(&recorder, 10, Some(path.clone()));
+        translator.translate("a", "FR").await.unwrap();
+
+        assert_eq!(recorder.calls(), ["a"]);
+        assert_eq!(load(&path).unwrap().len(), 1);
+    }
+}
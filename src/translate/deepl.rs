@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+use reqwest::header::AUTHORIZATION;
+use serde::Deserialize;
+
+use super::{read_json, TranslateError, Translator};
+
+pub const FREE_API_URL: &str = "https://api-free.deepl.com";
+pub const PRO_API_URL: &str = "https://api.deepl.com";
+
+#[derive(Deserialize, Debug)]
+struct Translation {
+    text: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DeeplResponse {
+    translations: Vec<Translation>,
+}
+
+// Free and Pro accounts share the same API, only the host differs.
+pub struct DeeplTranslator {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+}
+
+impl DeeplTranslator {
+    pub fn new(client: reqwest::Client, api_url: &str, api_key: &str) -> Self {
+        Self {
+            client,
+            url: format!("{}/v2/translate", api_url.trim_end_matches('/')),
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for DeeplTranslator {
+    async fn translate(&self, text: &str, target_lang: &str) -> Result<String, TranslateError> {
+        let target_lang = deepl_target_lang(target_lang);
+        let params = [("text", text), ("target_lang", target_lang.as_str())];
+
+        let resp = self
+            .client
+            .post(&self.url)
+            .form(&params)
+            .header(AUTHORIZATION, format!("DeepL-Auth-Key {}", self.api_key))
+            .send()
+            .await?;
+        let resp: DeeplResponse = read_json(resp).await?;
+
+        resp.translations
+            .into_iter()
+            .next()
+            .map(|translation| translation.text)
+            .ok_or(TranslateError::Empty)
+    }
+}
+
+// DeepL deprecated the variant-less English and Portuguese targets.
+fn deepl_target_lang(lang: &str) -> String {
+    match lang.to_uppercase().as_str() {
+        "EN" => "EN-GB".to_string(),
+        "PT" => "PT-PT".to_string(),
+        lang => lang.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    async fn server(response: ResponseTemplate) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/translate"))
+            .and(header("authorization", "DeepL-Auth-Key secret:fx"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    fn translator(server: &MockServer) -> DeeplTranslator {
+        DeeplTranslator::new(reqwest::Client::new(), &server.uri(), "secret:fx")
+    }
+
+    async fn sent_form(server: &MockServer) -> Vec<(String, String)> {
+        let requests = server.received_requests().await.unwrap();
+        url::form_urlencoded::parse(&requests[0].body)
+            .into_owned()
+            .collect()
+    }
+
+    fn translated(text: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "translations": [{ "detected_source_language": "EN", "text": text }],
+        }))
+    }
+
+    #[tokio::test]
+    async fn posts_a_form_with_the_auth_key() {
+        let server = server(translated("Serveurs en panne")).await;
+
+        let translation = translator(&server)
+            .translate("Servers are down", "fr")
+            .await
+            .unwrap();
+
+        assert_eq!(translation, "Serveurs en panne");
+        assert_eq!(
+            sent_form(&server).await,
+            [
+                ("text".to_string(), "Servers are down".to_string()),
+                ("target_lang".to_string(), "FR".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn maps_english_and_portuguese_to_a_variant() {
+        for (lang, expected) in [("EN", "EN-GB"), ("pt", "PT-PT"), ("EN-US", "EN-US")] {
+            let server = server(translated("Servers are down")).await;
+
+            translator(&server)
+                .translate("Servers are down", lang)
+                .await
+                .unwrap();
+
+            let form = sent_form(&server).await;
+            assert_eq!(form[1], ("target_lang".to_string(), expected.to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_errors_and_empty_responses() {
+        let failing = server(ResponseTemplate::new(403).set_body_string("Forbidden")).await;
+        let err = translator(&failing)
+            .translate("text", "FR")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TranslateError::Status { status, .. } if status == 403));
+
+        let empty =
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({ "translations": [] }));
+        let empty = server(empty).await;
+        let err = translator(&empty)
+            .translate("text", "FR")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TranslateError::Empty));
+    }
+}
@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{base_language, read_json, TranslateError, Translator};
+
+pub const API_URL: &str = "https://translation.googleapis.com";
+
+#[derive(Deserialize, Debug)]
+struct Translation {
+    #[serde(rename = "translatedText")]
+    translated_text: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct TranslationList {
+    translations: Vec<Translation>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GoogleResponse {
+    data: TranslationList,
+}
+
+// Cloud Translation v2 ("basic"), authenticated with an API key.
+pub struct GoogleTranslator {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+}
+
+impl GoogleTranslator {
+    pub fn new(client: reqwest::Client, api_url: &str, api_key: &str) -> Self {
+        Self {
+            client,
+            url: format!("{}/language/translate/v2", api_url.trim_end_matches('/')),
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for GoogleTranslator {
+    async fn translate(&self, text: &str, target_lang: &str) -> Result<String, TranslateError> {
+        let resp = self
+            .client
+            .post(&self.url)
+            .query(&[("key", self.api_key.as_str())])
+            // "text" keeps Google from HTML escaping the result
+            .json(&json!({
+                "q": text,
+                "target": base_language(target_lang),
+                "format": "text",
+            }))
+            .send()
+            .await?;
+        let resp: GoogleResponse = read_json(resp).await?;
+
+        resp.data
+            .translations
+            .into_iter()
+            .next()
+            .map(|translation| translation.translated_text)
+            .ok_or(TranslateError::Empty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{body_json, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn posts_a_v2_request_with_the_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/language/translate/v2"))
+            .and(query_param("key", "secret"))
+            .and(body_json(json!({
+                "q": "Servers are down <EU>",
+                "target": "pt",
+                "format": "text",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": {
+                    "translations": [{
+                        "translatedText": "Servidores em baixo <EU>",
+                        "detectedSourceLanguage": "en",
+                    }],
+                },
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translation = GoogleTranslator::new(reqwest::Client::new(), &server.uri(), "secret")
+            .translate("Servers are down <EU>", "PT-BR")
+            .await
+            .unwrap();
+
+        assert_eq!(translation, "Servidores em baixo <EU>");
+    }
+
+    #[tokio::test]
+    async fn rejects_unexpected_responses() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html>"))
+            .mount(&server)
+            .await;
+
+        let err = GoogleTranslator::new(reqwest::Client::new(), &server.uri(), "secret")
+            .translate("text", "FR")
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, TranslateError::Parse { .. }));
+    }
+}
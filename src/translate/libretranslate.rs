@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{base_language, read_json, TranslateError, Translator};
+
+#[derive(Deserialize, Debug)]
+struct LibreResponse {
+    #[serde(rename = "translatedText")]
+    translated_text: String,
+}
+
+// Works against libretranslate.com as well as self-hosted instances, which usually don't need an api key.
+pub struct LibreTranslator {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+}
+
+impl LibreTranslator {
+    pub fn new(client: reqwest::Client, api_url: &str, api_key: Option<String>) -> Self {
+        Self {
+            client,
+            url: format!("{}/translate", api_url.trim_end_matches('/')),
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for LibreTranslator {
+    async fn translate(&self, text: &str, target_lang: &str) -> Result<String, TranslateError> {
+        let mut body = json!({
+            "q": text,
+            "source": "auto",
+            "target": base_language(target_lang),
+            "format": "text",
+        });
+        if let Some(api_key) = &self.api_key {
+            body["api_key"] = json!(api_key);
+        }
+
+        let resp = self.client.post(&self.url).json(&body).send().await?;
+        let resp: LibreResponse = read_json(resp).await?;
+
+        Ok(resp.translated_text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{body_json, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    async fn server(body: serde_json::Value) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(body_json(body))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "translatedText": "Serveurs en panne",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    #[tokio::test]
+    async fn omits_the_api_key_when_unset() {
+        let server = server(json!({
+            "q": "Servers are down",
+            "source": "auto",
+            "target": "fr",
+            "format": "text",
+        }))
+        .await;
+
+        let translation = LibreTranslator::new(reqwest::Client::new(), &server.uri(), None)
+            .translate("Servers are down", "FR")
+            .await
+            .unwrap();
+
+        assert_eq!(translation, "Serveurs en panne");
+    }
+
+    #[tokio::test]
+    async fn sends_the_api_key_when_set() {
+        let server = server(json!({
+            "q": "Servers are down",
+            "source": "auto",
+            "target": "fr",
+            "format": "text",
+            "api_key": "secret",
+        }))
+        .await;
+
+        let translation = LibreTranslator::new(
+            reqwest::Client::new(),
+            &format!("{}/", server.uri()),
+            Some("secret".to_string()),
+        )
+        .translate("Servers are down", "fr-FR")
+        .await
+        .unwrap();
+
+        assert_eq!(translation, "Serveurs en panne");
+    }
+}
@@ -0,0 +1,171 @@
+use std::{collections::HashMap, num::NonZeroUsize};
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::{
+    config::{TranslatorBackend, TranslatorConfig},
+    http::body_snippet,
+    metrics,
+};
+
+pub mod cache;
+pub mod deepl;
+pub mod google;
+pub mod libretranslate;
+
+#[derive(Error, Debug)]
+pub enum TranslateError {
+    #[error("request to the translation API failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("translation API returned {status}: {snippet}")]
+    Status { status: StatusCode, snippet: String },
+    #[error("unexpected translation API response ({error}): {snippet}")]
+    Parse {
+        error: serde_json::Error,
+        snippet: String,
+    },
+    #[error("translation API returned no translation")]
+    Empty,
+}
+
+#[async_trait]
+pub trait Translator: Send + Sync {
+    // `target_lang` is the language code as written in the config, e.g. "FR" or "EN-GB".
+    async fn translate(&self, text: &str, target_lang: &str) -> Result<String, TranslateError>;
+}
+
+// Returns the text untranslated, used when no translation backend is configured.
+pub struct Passthrough;
+
+#[async_trait]
+impl Translator for Passthrough {
+    async fn translate(&self, text: &str, _target_lang: &str) -> Result<String, TranslateError> {
+        Ok(text.to_string())
+    }
+}
+
+// Counts the calls actually reaching a translation API, cache hits aside.
+struct MeteredTranslator {
+    inner: Box<dyn Translator>,
+    backend: &'static str,
+}
+
+#[async_trait]
+impl Translator for MeteredTranslator {
+    async fn translate(&self, text: &str, target_lang: &str) -> Result<String, TranslateError> {
+        let metrics = metrics::get();
+        let result = self.inner.translate(text, target_lang).await;
+
+        let outcome = if result.is_ok() { "success" } else { "failure" };
+        metrics
+            .translations
+            .with_label_values(&[self.backend, outcome])
+            .inc();
+        metrics
+            .translated_characters
+            .with_label_values(&[self.backend])
+            .inc_by(text.chars().count() as u64);
+
+        result
+    }
+}
+
+pub fn build(config: &TranslatorConfig, client: reqwest::Client) -> Box<dyn Translator> {
+    let translator: Box<dyn Translator> = Box::new(MeteredTranslator {
+        inner: build_backend(config, client),
+        backend: config.backend().name(),
+    });
+
+    match NonZeroUsize::new(config.cache.size) {
+        Some(size) if config.backend() != TranslatorBackend::Passthrough => Box::new(
+            cache::CachedTranslator::new(translator, size, config.cache.path.clone()),
+        ),
+        _ => translator,
+    }
+}
+
+fn build_backend(config: &TranslatorConfig, client: reqwest::Client) -> Box<dyn Translator> {
+    // api keys and urls were checked when loading the config
+    let api_key = config.api_key.clone().unwrap_or_default();
+
+    match config.backend() {
+        TranslatorBackend::DeeplFree => Box::new(deepl::DeeplTranslator::new(
+            client,
+            config.url.as_deref().unwrap_or(deepl::FREE_API_URL),
+            &api_key,
+        )),
+        TranslatorBackend::DeeplPro => Box::new(deepl::DeeplTranslator::new(
+            client,
+            config.url.as_deref().unwrap_or(deepl::PRO_API_URL),
+            &api_key,
+        )),
+        TranslatorBackend::Libretranslate => Box::new(libretranslate::LibreTranslator::new(
+            client,
+            config.url.as_deref().unwrap_or_default(),
+            config.api_key.clone(),
+        )),
+        TranslatorBackend::Google => Box::new(google::GoogleTranslator::new(
+            client,
+            config.url.as_deref().unwrap_or(google::API_URL),
+            &api_key,
+        )),
+        TranslatorBackend::Passthrough => Box::new(Passthrough),
+    }
+}
+
+// This function will attempt to translate. On fail it'll just return the same content, untranslated.
+pub async fn try_translate(translator: &dyn Translator, text: &str, target_lang: &str) -> String {
+    match translator.translate(text, target_lang).await {
+        Ok(translation) => translation,
+        Err(e) => {
+            error!("Failed to translate to {target_lang}, posting the original text: {e}");
+            text.to_string()
+        }
+    }
+}
+
+// Translates once per distinct language, keyed by language. Texts seen before are served by the cache.
+pub async fn translate_all<'a>(
+    translator: &dyn Translator,
+    text: &str,
+    languages: impl IntoIterator<Item = &'a str>,
+) -> HashMap<String, String> {
+    let mut translations = HashMap::new();
+
+    for language in languages {
+        if translations.contains_key(language) {
+            continue;
+        }
+
+        let translation = try_translate(translator, text, language).await;
+        translations.insert(language.to_string(), translation);
+    }
+
+    translations
+}
+
+// Reads a JSON response without trusting it to be one, translation APIs answer errors in various shapes.
+async fn read_json<T: DeserializeOwned>(resp: reqwest::Response) -> Result<T, TranslateError> {
+    let status = resp.status();
+    let body = resp.text().await?;
+
+    if !status.is_success() {
+        return Err(TranslateError::Status {
+            status,
+            snippet: body_snippet(&body),
+        });
+    }
+
+    serde_json::from_str(&body).map_err(|error| TranslateError::Parse {
+        error,
+        snippet: body_snippet(&body),
+    })
+}
+
+// "EN-GB" -> "en", for backends only knowing about base languages.
+fn base_language(lang: &str) -> String {
+    lang.split(['-', '_']).next().unwrap_or(lang).to_lowercase()
+}
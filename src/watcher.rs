@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use futures::{stream, Stream, StreamExt};
+use tokio::time::{self, MissedTickBehavior};
+
+use crate::{
+    models::Event,
+    retry::RetryPolicy,
+    source::StatusSource,
+    state::{self, EventChanges, ForgetPolicy, SavedEvent, SavedEvents},
+    status::StatusError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    New,
+    Changed,
+    Resolved,
+}
+
+impl ChangeKind {
+    /// Metrics label.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Changed => "changed",
+            Self::Resolved => "resolved",
+        }
+    }
+}
+
+/// What happened to an event since it was last seen.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub event: Event,
+    /// Fields that changed, all false for new events.
+    pub changes: EventChanges,
+}
+
+/// Compares an event with what was saved about it, None when nothing we display changed.
+pub fn detect(saved_event: Option<&SavedEvent>, event: &Event) -> Option<Change> {
+    let Some(saved_event) = saved_event else {
+        return Some(Change {
+            kind: ChangeKind::New,
+            event: event.clone(),
+            changes: EventChanges::default(),
+        });
+    };
+
+    let changes = saved_event.changes(event);
+    if !changes.any() {
+        return None;
+    }
+    let kind = if changes.solve_time && event.solve_time.is_some() {
+        ChangeKind::Resolved
+    } else {
+        ChangeKind::Changed
+    };
+
+    Some(Change {
+        kind,
+        event: event.clone(),
+        changes,
+    })
+}
+
+/// Polls a status source and reports the events that appeared or changed, without sending anything.
+/// For applications doing their own notifications, e.g. a Discord bot.
+pub struct Watcher {
+    source: Box<dyn StatusSource>,
+    retry_policy: RetryPolicy,
+    forget_policy: ForgetPolicy,
+    seen: SavedEvents,
+}
+
+impl Watcher {
+    /// Seen events are only kept in memory, `forget_policy` tells when the ones missing from the list are dropped.
+    pub fn new(
+        source: Box<dyn StatusSource>,
+        retry_policy: RetryPolicy,
+        forget_policy: ForgetPolicy,
+    ) -> Self {
+        Self {
+            source,
+            retry_policy,
+            forget_policy,
+            seen: SavedEvents::new(),
+        }
+    }
+
+    /// Fetches the events and returns what changed since the previous poll.
+    /// Every event listed on the first poll is new.
+    pub async fn poll(&mut self) -> Result<Vec<Change>, StatusError> {
+        let events = self.source.messages_with_retry(&self.retry_policy).await?;
+
+        let changes: Vec<Change> = events
+            .iter()
+            .filter_map(|event| detect(self.seen.get(&event.id), event))
+            .collect();
+        for change in changes.iter() {
+            let event = &change.event;
+            self.seen.insert(
+                event.id.clone(),
+                SavedEvent {
+                    content: event.content.clone(),
+                    event_type: event.event_type,
+                    solve_time: event.solve_time,
+                    ..Default::default()
+                },
+            );
+        }
+        state::forget_missing(&mut self.seen, &events, &self.forget_policy, Utc::now());
+
+        Ok(changes)
+    }
+
+    /// Polls every `interval`, forever. A failed poll shows up as an error, the next ones still happen.
+    pub fn into_stream(
+        self,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<Change, StatusError>> {
+        let mut ticker = time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        stream::unfold((self, ticker), |(mut watcher, mut ticker)| async move {
+            ticker.tick().await;
+            let items: Vec<Result<Change, StatusError>> = match watcher.poll().await {
+                Ok(changes) => changes.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            Some((stream::iter(items), (watcher, ticker)))
+        })
+        .flatten()
+    }
+}
@@ -0,0 +1,92 @@
+// Fixtures shared by the integration tests, each test binary only uses some of them.
+#![allow(dead_code)]
+
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tarkov_status::{Event, EventType, StatusError};
+use wiremock::{
+    matchers::{method, path, path_regex, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+pub const WEBHOOK_PATH: &str = "/api/webhooks/1/token";
+
+// A Discord webhook answering every message with id 1001 and accepting every edit.
+pub async fn discord() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(WEBHOOK_PATH))
+        .and(query_param("wait", "true"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "id": "1001", "channel_id": "7" })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path_regex(format!("^{WEBHOOK_PATH}/messages/\\d+$")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .mount(&server)
+        .await;
+    server
+}
+
+pub fn webhook_url(discord: &MockServer) -> String {
+    format!("{}{WEBHOOK_PATH}", discord.uri())
+}
+
+// Messages posted or edited so far, as (method, path, body).
+pub async fn deliveries(discord: &MockServer) -> Vec<(String, String, Value)> {
+    discord
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            (
+                request.method.to_string(),
+                request.url.path().to_string(),
+                serde_json::from_slice(&request.body).unwrap(),
+            )
+        })
+        .collect()
+}
+
+pub fn embed(body: &Value) -> &Value {
+    &body["embeds"][0]
+}
+
+pub fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, hour, minute, 0).unwrap()
+}
+
+// Server issues from 10:00, resolved at 11:30 when `solved`.
+pub fn incident(content: &str, solved: bool) -> Event {
+    Event {
+        id: "incident-1".to_string(),
+        content: content.to_string(),
+        event_type: EventType::ServerIssues,
+        time: at(10, 0),
+        solve_time: solved.then(|| at(11, 30)),
+        start_time: None,
+        end_time: None,
+    }
+}
+
+pub fn maintenance() -> Event {
+    Event {
+        id: "maintenance-1".to_string(),
+        content: "Maintenance from 14:00 to 16:00 UTC".to_string(),
+        event_type: EventType::Maintenance,
+        ..incident("", false)
+    }
+}
+
+// What the status API answers with while it's down.
+pub fn outage() -> StatusError {
+    StatusError::Status {
+        status: StatusCode::BAD_GATEWAY,
+        snippet: "<html>Bad gateway</html>".to_string(),
+        retry_after: None,
+    }
+}
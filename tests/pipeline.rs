@@ -0,0 +1,370 @@
+// End-to-end runs of the `once` command against local stand-ins for the status API, DeepL and a Discord webhook.
+
+mod common;
+
+use std::{fs, process::Output};
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, Request, Respond, ResponseTemplate,
+};
+
+use common::{embed, WEBHOOK_PATH};
+
+// Prefixes the text with the target language, e.g. "[FR] Servers are down".
+struct EchoTranslation;
+
+impl Respond for EchoTranslation {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let params: Vec<(String, String)> = url::form_urlencoded::parse(&request.body)
+            .into_owned()
+            .collect();
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        };
+
+        ResponseTemplate::new(200).set_body_json(json!({
+            "translations": [{ "text": format!("[{}] {}", param("target_lang"), param("text")) }]
+        }))
+    }
+}
+
+struct Harness {
+    status: MockServer,
+    deepl: MockServer,
+    discord: MockServer,
+    dir: TempDir,
+    // Deliveries already looked at by `deliveries`.
+    seen: usize,
+}
+
+impl Harness {
+    async fn start() -> Self {
+        let harness = Self {
+            status: MockServer::start().await,
+            deepl: MockServer::start().await,
+            discord: common::discord().await,
+            dir: TempDir::new().unwrap(),
+            seen: 0,
+        };
+
+        Mock::given(method("POST"))
+            .and(path("/v2/translate"))
+            .respond_with(EchoTranslation)
+            .mount(&harness.deepl)
+            .await;
+
+        let config = format!(
+            r#"
+poll_interval = 30
+status_api_url = "{status}"
+target_lang = "FR"
+
+[translator]
+backend = "deepl-free"
+api_key = "test:fx"
+url = "{deepl}"
+
+[translator.cache]
+size = 0
+
+[state]
+backend = "json"
+path = "{state}"
+# A single poll treated as "every event is gone" would be enough to forget them
+forget_after_polls = 1
+
+[services]
+enabled = false
+
+[maintenance]
+enabled = false
+
+[retry]
+max_attempts = 1
+initial_delay_ms = 10
+max_delay_ms = 10
+
+[delivery_retry]
+max_attempts = 1
+initial_delay_ms = 10
+max_delay_ms = 10
+
+[[destinations]]
+name = "discord"
+url = "{discord}"
+"#,
+            status = harness.status.uri(),
+            deepl = harness.deepl.uri(),
+            discord = common::webhook_url(&harness.discord),
+            state = harness.dir.path().join("state.json").display(),
+        );
+        fs::write(harness.config_path(), config).unwrap();
+
+        harness
+    }
+
+    fn config_path(&self) -> std::path::PathBuf {
+        self.dir.path().join("config.toml")
+    }
+
+    // What the status API lists from now on.
+    async fn list(&self, events: Value) {
+        self.status.reset().await;
+        Mock::given(method("GET"))
+            .and(path("/api/message/list"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(events))
+            .mount(&self.status)
+            .await;
+    }
+
+    async fn status_down(&self) {
+        self.status.reset().await;
+        Mock::given(method("GET"))
+            .and(path("/api/message/list"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("<html>Bad gateway</html>"))
+            .mount(&self.status)
+            .await;
+    }
+
+    async fn translation_down(&self) {
+        self.deepl.reset().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/translate"))
+            .respond_with(ResponseTemplate::new(456).set_body_json(json!({
+                "message": "Quota exceeded"
+            })))
+            .mount(&self.deepl)
+            .await;
+    }
+
+    // Each run is a separate process, so every run after the first one is a restart.
+    async fn once(&self) -> Output {
+        self.command("once").await
+    }
+
+    async fn command(&self, command: &'static str) -> Output {
+        let config = self.config_path();
+        tokio::task::spawn_blocking(move || {
+            std::process::Command::new(env!("CARGO_BIN_EXE_tarkov-status"))
+                .env_clear()
+                .env("RUST_LOG", "info")
+                .arg("--config")
+                .arg(config)
+                .arg(command)
+                .output()
+                .unwrap()
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn once_ok(&self) {
+        let output = self.once().await;
+        assert!(
+            output.status.success(),
+            "once failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    // Messages posted or edited since the last call, as (method, path, body).
+    async fn deliveries(&mut self) -> Vec<(String, String, Value)> {
+        let deliveries = common::deliveries(&self.discord).await;
+        let new = deliveries[self.seen..].to_vec();
+        self.seen = deliveries.len();
+
+        new
+    }
+}
+
+fn event(content: &str, solve_time: Option<&str>) -> Value {
+    json!({
+        "_id": "incident-1",
+        "content": content,
+        "type": 2,
+        "time": "2024-01-01T10:00:00Z",
+        "solveTime": solve_time,
+    })
+}
+
+fn field_names(body: &Value) -> Vec<&str> {
+    embed(body)["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn new_incident_is_posted_translated() {
+    let mut harness = Harness::start().await;
+    harness.list(json!([event("Servers are down", None)])).await;
+
+    harness.once_ok().await;
+
+    let deliveries = harness.deliveries().await;
+    assert_eq!(deliveries.len(), 1);
+    let (method, path, body) = &deliveries[0];
+    assert_eq!(method, "POST");
+    assert_eq!(path, WEBHOOK_PATH);
+    assert_eq!(embed(body)["title"], "Problèmes de serveur");
+    assert_eq!(embed(body)["description"], "[FR] Servers are down");
+    assert_eq!(field_names(body), ["Depuis", "Status"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn content_update_edits_the_message() {
+    let mut harness = Harness::start().await;
+    harness.list(json!([event("Servers are down", None)])).await;
+    harness.once_ok().await;
+    harness.deliveries().await;
+
+    harness
+        .list(json!([event(
+            "Servers are down, a fix is on its way",
+            None
+        )]))
+        .await;
+    harness.once_ok().await;
+
+    let deliveries = harness.deliveries().await;
+    assert_eq!(deliveries.len(), 1);
+    let (method, path, body) = &deliveries[0];
+    assert_eq!(method, "PATCH");
+    assert_eq!(path, &format!("{WEBHOOK_PATH}/messages/1001"));
+    assert_eq!(
+        embed(body)["description"],
+        "[FR] Servers are down, a fix is on its way"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn resolution_sums_up_the_incident() {
+    let mut harness = Harness::start().await;
+    harness.list(json!([event("Servers are down", None)])).await;
+    harness.once_ok().await;
+    harness.deliveries().await;
+
+    harness
+        .list(json!([event(
+            "Servers are down",
+            Some("2024-01-01T11:30:00Z")
+        )]))
+        .await;
+    harness.once_ok().await;
+
+    let deliveries = harness.deliveries().await;
+    assert_eq!(deliveries.len(), 1);
+    let (method, path, body) = &deliveries[0];
+    assert_eq!(method, "PATCH");
+    assert_eq!(path, &format!("{WEBHOOK_PATH}/messages/1001"));
+    assert_eq!(
+        field_names(body),
+        ["Résolu depuis", "Début", "Durée", "Mises à jour", "Status"]
+    );
+    let fields = embed(body)["fields"].as_array().unwrap();
+    assert_eq!(fields[2]["value"], "1 h 30 min");
+    // Never edited, the resolution doesn't count as an update
+    assert_eq!(fields[3]["value"], "0");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_outage_sends_nothing_and_keeps_the_state() {
+    let mut harness = Harness::start().await;
+    harness.list(json!([event("Servers are down", None)])).await;
+    harness.once_ok().await;
+    harness.deliveries().await;
+
+    harness.status_down().await;
+    let output = harness.once().await;
+    assert!(!output.status.success());
+    assert!(harness.deliveries().await.is_empty());
+
+    // The event wasn't forgotten because of the outage, so it isn't posted again
+    harness.list(json!([event("Servers are down", None)])).await;
+    harness.once_ok().await;
+    assert!(harness.deliveries().await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn translation_failure_posts_the_original_text() {
+    let mut harness = Harness::start().await;
+    harness.translation_down().await;
+    harness.list(json!([event("Servers are down", None)])).await;
+
+    harness.once_ok().await;
+
+    let deliveries = harness.deliveries().await;
+    assert_eq!(deliveries.len(), 1);
+    let (method, _, body) = &deliveries[0];
+    assert_eq!(method, "POST");
+    assert_eq!(embed(body)["description"], "Servers are down");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn restart_does_not_repost_known_events() {
+    let mut harness = Harness::start().await;
+    harness.list(json!([event("Servers are down", None)])).await;
+    harness.once_ok().await;
+    assert_eq!(harness.deliveries().await.len(), 1);
+
+    harness.once_ok().await;
+    harness.once_ok().await;
+
+    assert!(harness.deliveries().await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_delivery_is_retried_on_the_next_poll() {
+    let mut harness = Harness::start().await;
+    Mock::given(method("POST"))
+        .and(path(WEBHOOK_PATH))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&harness.discord)
+        .await;
+    harness.list(json!([event("Servers are down", None)])).await;
+
+    harness.once_ok().await;
+    assert_eq!(harness.deliveries().await.len(), 1);
+
+    // Same content, only the destination that missed it gets it
+    harness.once_ok().await;
+    let deliveries = harness.deliveries().await;
+    assert_eq!(deliveries.len(), 1);
+    let (method, path, body) = &deliveries[0];
+    assert_eq!(method, "POST");
+    assert_eq!(path, WEBHOOK_PATH);
+    assert_eq!(embed(body)["description"], "[FR] Servers are down");
+
+    harness.once_ok().await;
+    assert!(harness.deliveries().await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dry_run_prints_the_edits() {
+    let mut harness = Harness::start().await;
+    harness.list(json!([event("Servers are down", None)])).await;
+    harness.once_ok().await;
+    harness.deliveries().await;
+
+    harness
+        .list(json!([event("Servers are back up", None)]))
+        .await;
+    let output = harness.command("dry-run").await;
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[discord] edit of message 1001"));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("no longer exists"));
+    assert!(harness.deliveries().await.is_empty());
+}
@@ -0,0 +1,146 @@
+// The whole pipeline run in-process against scripted snapshots of the status page, delivering to a local
+// stand-in for a Discord webhook.
+
+mod common;
+
+use std::time::Duration;
+
+use serde_json::json;
+use tarkov_status::{
+    app::App,
+    config::{Config, DestinationConfig, NotifierKind, RetryConfig, StateBackend},
+    source::ScriptedSource,
+    Event, StatusError,
+};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use common::{deliveries, discord, embed, incident, outage, webhook_url, WEBHOOK_PATH};
+
+// Untranslated, in memory, without services or maintenance reminders, and a single attempt at everything.
+fn config(discord: &MockServer) -> Config {
+    let once = RetryConfig {
+        max_attempts: 1,
+        initial_delay_ms: 10,
+        max_delay_ms: 10,
+    };
+
+    let mut config = Config {
+        retry: once.clone(),
+        delivery_retry: once,
+        destinations: vec![DestinationConfig {
+            url: Some(webhook_url(discord)),
+            ..DestinationConfig::new("discord", NotifierKind::Discord)
+        }],
+        ..Default::default()
+    };
+    config.state.backend = StateBackend::Memory;
+    config.services.enabled = false;
+    config.maintenance.enabled = false;
+    config
+}
+
+#[tokio::test]
+async fn incident_lifecycle() {
+    let discord = discord().await;
+    let mut app = App::new(config(&discord), false).unwrap();
+    app.set_source(Box::new(ScriptedSource::new([
+        Ok(vec![incident("Servers are down", false)]),
+        Err(outage()),
+        Ok(vec![incident(
+            "Servers are down, a fix is on its way",
+            false,
+        )]),
+        Ok(vec![incident(
+            "Servers are down, a fix is on its way",
+            true,
+        )]),
+        Ok(vec![incident(
+            "Servers are down, a fix is on its way",
+            true,
+        )]),
+    ])));
+
+    // New
+    app.poll().await.unwrap();
+    let sent = deliveries(&discord).await;
+    assert_eq!(sent.len(), 1);
+    let (method, path, body) = &sent[0];
+    assert_eq!(method, "POST");
+    assert_eq!(path, WEBHOOK_PATH);
+    assert_eq!(embed(body)["description"], "Servers are down");
+
+    // The outage neither sends anything nor forgets the event
+    assert!(matches!(app.poll().await, Err(StatusError::Status { .. })));
+    assert_eq!(deliveries(&discord).await.len(), 1);
+
+    // Changed, the message is edited instead of posting a new one
+    app.poll().await.unwrap();
+    let sent = deliveries(&discord).await;
+    assert_eq!(sent.len(), 2);
+    let (method, path, body) = &sent[1];
+    assert_eq!(method, "PATCH");
+    assert_eq!(path, &format!("{WEBHOOK_PATH}/messages/1001"));
+    assert_eq!(
+        embed(body)["description"],
+        "Servers are down, a fix is on its way"
+    );
+
+    // Resolved
+    app.poll().await.unwrap();
+    let sent = deliveries(&discord).await;
+    assert_eq!(sent.len(), 3);
+    let (method, path, body) = &sent[2];
+    assert_eq!(method, "PATCH");
+    assert_eq!(path, &format!("{WEBHOOK_PATH}/messages/1001"));
+    assert_eq!(embed(body)["fields"][0]["name"], "Résolu depuis");
+    // The content was edited once, the resolution isn't an update
+    assert_eq!(embed(body)["fields"][3]["name"], "Mises à jour");
+    assert_eq!(embed(body)["fields"][3]["value"], "1");
+
+    // Nothing left to say
+    app.poll().await.unwrap();
+    assert_eq!(deliveries(&discord).await.len(), 3);
+}
+
+#[tokio::test]
+async fn slow_destination_does_not_hold_back_the_others() {
+    let fast = discord().await;
+    let slow = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(WEBHOOK_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "id": "2001", "channel_id": "7" }))
+                .set_delay(Duration::from_secs(2)),
+        )
+        .mount(&slow)
+        .await;
+
+    let mut config = config(&fast);
+    let mut slow_destination = config.destinations[0].clone();
+    slow_destination.name = "slow".to_string();
+    slow_destination.url = Some(webhook_url(&slow));
+    config.destinations.push(slow_destination);
+
+    let mut app = App::new(config, false).unwrap();
+    app.set_source(Box::new(ScriptedSource::new([Ok(vec![
+        incident("Servers are down", false),
+        Event {
+            id: "incident-2".to_string(),
+            ..incident("Matchmaking is down", false)
+        },
+    ])])));
+
+    let (polled, sent_meanwhile) = tokio::join!(app.poll(), async {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        deliveries(&fast).await.len()
+    });
+
+    polled.unwrap();
+    // Both events reached the fast destination while the slow one was still busy with the first
+    assert_eq!(sent_meanwhile, 2);
+    assert_eq!(deliveries(&slow).await.len(), 2);
+}
@@ -0,0 +1,89 @@
+// The library side: a `Watcher` over scripted snapshots, consumed as a stream like a bot would.
+
+mod common;
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use tarkov_status::{
+    retry::RetryPolicy, source::ScriptedSource, state::ForgetPolicy, ChangeKind, StatusError,
+    Watcher,
+};
+
+use common::{incident, maintenance, outage};
+
+fn watcher(source: ScriptedSource) -> Watcher {
+    let policy = RetryPolicy {
+        max_attempts: 1,
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(10),
+    };
+    Watcher::new(Box::new(source), policy, ForgetPolicy::default())
+}
+
+#[tokio::test]
+async fn stream_reports_each_change_once() {
+    let source = ScriptedSource::new([
+        Ok(vec![incident("Servers are down", false)]),
+        Err(outage()),
+        Ok(vec![
+            incident("Servers are down, a fix is on its way", false),
+            maintenance(),
+        ]),
+        Ok(vec![
+            incident("Servers are down, a fix is on its way", true),
+            maintenance(),
+        ]),
+    ]);
+
+    let items: Vec<Result<(ChangeKind, String), StatusError>> = watcher(source)
+        .into_stream(Duration::from_millis(10))
+        .map(|item| item.map(|change| (change.kind, change.event.id)))
+        .take(5)
+        .collect()
+        .await;
+
+    let mut items = items.into_iter();
+    assert_eq!(
+        items.next().unwrap().unwrap(),
+        (ChangeKind::New, "incident-1".to_string())
+    );
+    // The outage shows up in the stream without ending it
+    assert!(matches!(
+        items.next().unwrap(),
+        Err(StatusError::Status { .. })
+    ));
+    assert_eq!(
+        items.next().unwrap().unwrap(),
+        (ChangeKind::Changed, "incident-1".to_string())
+    );
+    assert_eq!(
+        items.next().unwrap().unwrap(),
+        (ChangeKind::New, "maintenance-1".to_string())
+    );
+    assert_eq!(
+        items.next().unwrap().unwrap(),
+        (ChangeKind::Resolved, "incident-1".to_string())
+    );
+}
+
+#[tokio::test]
+async fn poll_only_reports_what_changed() {
+    let mut watcher = watcher(ScriptedSource::new([
+        Ok(vec![incident("Servers are down", false), maintenance()]),
+        Ok(vec![incident("Servers are down", false), maintenance()]),
+        Ok(vec![incident("Servers are back up", false), maintenance()]),
+    ]));
+
+    let changes = watcher.poll().await.unwrap();
+    assert_eq!(changes.len(), 2);
+    assert!(changes.iter().all(|change| change.kind == ChangeKind::New));
+
+    assert!(watcher.poll().await.unwrap().is_empty());
+
+    let changes = watcher.poll().await.unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].kind, ChangeKind::Changed);
+    assert!(changes[0].changes.content);
+    assert_eq!(changes[0].event.content, "Servers are back up");
+}